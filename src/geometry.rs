@@ -1,4 +1,6 @@
 // This code was basically taken from Xilem/Kurbo.
+use std::ops::{Add, Sub, Mul};
+
 pub trait FloatExt {
     fn expand(&self) -> f32;
 }
@@ -30,6 +32,25 @@ pub struct Point {
     pub y: f32
 }
 
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Padding {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Axis {
+    Horizontal,
+    Vertical
+}
+
+/// Corner radii in the order top left, top right,
+/// bottom right, bottom left.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct BorderRadius(pub [f32; 4]);
+
 impl Size {
     pub const ZERO: Size = Size::new(0f32, 0f32);
 
@@ -56,6 +77,98 @@ impl Size {
 }
 
 impl Rect {
+    #[inline]
+    pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    /// A rect of the given `size` positioned at the origin.
+    #[inline]
+    pub const fn from_size(size: Size) -> Self {
+        Self::new(0f32, 0f32, size.width, size.height)
+    }
+
+    #[inline]
+    pub fn origin(&self) -> Point {
+        Point::new(self.x, self.y)
+    }
+
+    /// Whether `point` lies inside the rect. The right and bottom
+    /// edges are exclusive so that adjacent rects never both contain
+    /// the same point.
+    #[inline]
+    pub fn contains(&self, point: Point) -> bool {
+        point.x >= self.x &&
+            point.x < self.x + self.width &&
+            point.y >= self.y &&
+            point.y < self.y + self.height
+    }
+
+    /// Whether the two rects share any area. Rects that only
+    /// touch at an edge do not overlap.
+    #[inline]
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        self.x < other.x + other.width &&
+            other.x < self.x + self.width &&
+            self.y < other.y + other.height &&
+            other.y < self.y + self.height
+    }
+
+    /// The area shared by both rects or `None` if they don't overlap.
+    #[inline]
+    pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+        if !self.overlaps(other) {
+            return None;
+        }
+
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+
+        Some(Self::new(x, y, right - x, bottom - y))
+    }
+
+    /// Grows the rect by `dx` on the left and right sides
+    /// and by `dy` on the top and bottom sides.
+    #[must_use]
+    #[inline]
+    pub fn inflate(&self, dx: f32, dy: f32) -> Rect {
+        let width = (self.width + dx * 2f32).max(0f32);
+        let height = (self.height + dy * 2f32).max(0f32);
+
+        // Same as moving by -dx and -dy, except when shrinking by
+        // more than the size, which collapses the rect to its center.
+        Self {
+            x: self.x + (self.width - width) / 2f32,
+            y: self.y + (self.height - height) / 2f32,
+            width,
+            height
+        }
+    }
+
+    /// Shrinks the rect by `dx` on the left and right sides
+    /// and by `dy` on the top and bottom sides. Shrinking by
+    /// more than half the size collapses the rect to its center.
+    #[must_use]
+    #[inline]
+    pub fn deflate(&self, dx: f32, dy: f32) -> Rect {
+        self.inflate(-dx, -dy)
+    }
+
+    /// Shrinks the rect by the given `padding` on each side.
+    /// The size never becomes negative.
+    #[must_use]
+    #[inline]
+    pub fn inset(&self, padding: Padding) -> Rect {
+        Self {
+            x: self.x + padding.left,
+            y: self.y + padding.top,
+            width: (self.width - padding.horizontal()).max(0f32),
+            height: (self.height - padding.vertical()).max(0f32)
+        }
+    }
+
     #[inline]
     pub fn set_size(&mut self, size: Size) {
         self.width = size.width;
@@ -110,9 +223,274 @@ impl Point {
     }
 }
 
+impl Add for Size {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.width + rhs.width, self.height + rhs.height)
+    }
+}
+
+impl Sub for Size {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.width - rhs.width, self.height - rhs.height)
+    }
+}
+
+impl Mul<f32> for Size {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: f32) -> Self {
+        Self::new(self.width * rhs, self.height * rhs)
+    }
+}
+
+impl Add for Point {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Add<Size> for Point {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Size) -> Self {
+        Self::new(self.x + rhs.width, self.y + rhs.height)
+    }
+}
+
+impl Padding {
+    pub const ZERO: Self = Self::all(0f32);
+
+    #[inline]
+    pub const fn new(top: f32, right: f32, bottom: f32, left: f32) -> Self {
+        Self { top, right, bottom, left }
+    }
+
+    #[inline]
+    pub const fn all(padding: f32) -> Self {
+        Self::new(padding, padding, padding, padding)
+    }
+
+    /// `vertical` is applied to the top and bottom sides
+    /// and `horizontal` to the left and right sides.
+    #[inline]
+    pub const fn symmetric(vertical: f32, horizontal: f32) -> Self {
+        Self::new(vertical, horizontal, vertical, horizontal)
+    }
+
+    /// Total padding on the top and bottom sides.
+    #[inline]
+    pub fn vertical(&self) -> f32 {
+        self.top + self.bottom
+    }
+
+    /// Total padding on the left and right sides.
+    #[inline]
+    pub fn horizontal(&self) -> f32 {
+        self.left + self.right
+    }
+
+    /// Total padding along the given `axis`.
+    #[inline]
+    pub fn axis(&self, axis: Axis) -> f32 {
+        match axis {
+            Axis::Horizontal => self.horizontal(),
+            Axis::Vertical => self.vertical()
+        }
+    }
+
+    /// The space taken up by the padding on both axes.
+    #[inline]
+    pub fn size(&self) -> Size {
+        Size::new(self.horizontal(), self.vertical())
+    }
+}
+
+impl From<f32> for Padding {
+    #[inline]
+    fn from(value: f32) -> Self {
+        Self::all(value)
+    }
+}
+
+impl From<(f32, f32)> for Padding {
+    /// Same as [`Padding::symmetric`].
+    #[inline]
+    fn from((vertical, horizontal): (f32, f32)) -> Self {
+        Self::symmetric(vertical, horizontal)
+    }
+}
+
+impl BorderRadius {
+    pub const ZERO: Self = Self::all(0f32);
+
+    #[inline]
+    pub const fn new(
+        top_left: f32,
+        top_right: f32,
+        bottom_right: f32,
+        bottom_left: f32
+    ) -> Self {
+        Self([top_left, top_right, bottom_right, bottom_left])
+    }
+
+    #[inline]
+    pub const fn all(radius: f32) -> Self {
+        Self([radius; 4])
+    }
+
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        self.0.iter().all(|x| *x <= 0f32)
+    }
+}
+
+impl From<f32> for BorderRadius {
+    #[inline]
+    fn from(value: f32) -> Self {
+        Self::all(value)
+    }
+}
+
+impl Axis {
+    #[inline]
+    pub fn flip(&self) -> Self {
+        match self {
+            Self::Horizontal => Self::Vertical,
+            Self::Vertical => Self::Horizontal
+        }
+    }
+
+    #[inline]
+    pub fn main(&self, size: Size) -> f32 {
+        match self {
+            Self::Horizontal => size.width,
+            Self::Vertical => size.height
+        }
+    }
+
+    #[inline]
+    pub fn cross(&self, size: Size) -> f32 {
+        match self {
+            Self::Horizontal => size.height,
+            Self::Vertical => size.width
+        }
+    }
+
+    #[inline]
+    pub fn main_and_cross_size(&self, size: Size) -> (f32, f32) {
+        match self {
+            Self::Horizontal => (size.width, size.height),
+            Self::Vertical => (size.height, size.width)
+        }
+    }
+
+    #[inline]
+    pub fn main_and_cross(&self, main: f32, cross: f32) -> (f32, f32) {
+        match self {
+            Self::Horizontal => (main, cross),
+            Self::Vertical => (cross, main)
+        }
+    }
+}
+
 impl FloatExt for f32 {
     #[inline]
     fn expand(&self) -> f32 {
         self.abs().ceil().copysign(*self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECT: Rect = Rect::new(10f32, 20f32, 30f32, 40f32);
+
+    #[test]
+    fn contains() {
+        assert!(RECT.contains(Point::new(10f32, 20f32)));
+        assert!(RECT.contains(Point::new(39.9f32, 59.9f32)));
+
+        // The right and bottom edges are exclusive.
+        assert!(!RECT.contains(Point::new(40f32, 30f32)));
+        assert!(!RECT.contains(Point::new(20f32, 60f32)));
+        assert!(!RECT.contains(Point::new(9.9f32, 30f32)));
+        assert!(!RECT.contains(Point::new(20f32, 19.9f32)));
+    }
+
+    #[test]
+    fn overlaps() {
+        assert!(RECT.overlaps(&Rect::new(30f32, 50f32, 20f32, 20f32)));
+        assert!(RECT.overlaps(&Rect::new(15f32, 25f32, 5f32, 5f32)));
+
+        // Rects that only touch at an edge or corner.
+        assert!(!RECT.overlaps(&Rect::new(40f32, 20f32, 10f32, 10f32)));
+        assert!(!RECT.overlaps(&Rect::new(0f32, 20f32, 10f32, 10f32)));
+        assert!(!RECT.overlaps(&Rect::new(10f32, 60f32, 10f32, 10f32)));
+        assert!(!RECT.overlaps(&Rect::new(40f32, 60f32, 10f32, 10f32)));
+    }
+
+    #[test]
+    fn intersect() {
+        assert_eq!(
+            RECT.intersect(&Rect::new(30f32, 50f32, 20f32, 20f32)),
+            Some(Rect::new(30f32, 50f32, 10f32, 10f32))
+        );
+        assert_eq!(
+            RECT.intersect(&Rect::new(0f32, 0f32, 100f32, 100f32)),
+            Some(RECT)
+        );
+        assert_eq!(RECT.intersect(&Rect::new(40f32, 20f32, 10f32, 10f32)), None);
+    }
+
+    #[test]
+    fn inflate_and_deflate() {
+        assert_eq!(RECT.inflate(5f32, 10f32), Rect::new(5f32, 10f32, 40f32, 60f32));
+        assert_eq!(RECT.deflate(5f32, 10f32), Rect::new(15f32, 30f32, 20f32, 20f32));
+        assert_eq!(RECT.inflate(5f32, 10f32).deflate(5f32, 10f32), RECT);
+
+        // Collapses to the center instead of moving past it.
+        assert_eq!(RECT.deflate(20f32, 30f32), Rect::new(25f32, 40f32, 0f32, 0f32));
+    }
+
+    #[test]
+    fn inset() {
+        assert_eq!(
+            RECT.inset(Padding::new(1f32, 2f32, 3f32, 4f32)),
+            Rect::new(14f32, 21f32, 24f32, 36f32)
+        );
+    }
+
+    #[test]
+    fn operators() {
+        let size = Size::new(3f32, 4f32);
+        let point = Point::new(1f32, 2f32);
+
+        assert_eq!(size + Size::new(1f32, 2f32), Size::new(4f32, 6f32));
+        assert_eq!(size - Size::new(1f32, 2f32), Size::new(2f32, 2f32));
+        assert_eq!(size * 2f32, Size::new(6f32, 8f32));
+        assert_eq!(point + Point::new(3f32, 4f32), Point::new(4f32, 6f32));
+        assert_eq!(point - Point::new(3f32, 4f32), Point::new(-2f32, -2f32));
+        assert_eq!(point + size, Point::new(4f32, 6f32));
+    }
+}
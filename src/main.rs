@@ -3,15 +3,20 @@ mod geometry;
 mod widget;
 mod theme;
 mod positioner;
+mod sys_info;
+#[cfg(feature = "profiling")]
+mod profiler;
+
+use std::{fmt, error::Error, process, thread, time::Duration};
 
 use smithay_client_toolkit::{
     reexports::client::{
-        globals::registry_queue_init,
-        protocol::{wl_output, wl_seat, wl_surface, wl_shm},
-        Connection, QueueHandle,
+        globals::{registry_queue_init, GlobalError, BindError},
+        protocol::{wl_output, wl_seat, wl_surface, wl_shm, wl_callback},
+        Connection, QueueHandle, Dispatch, ConnectError, DispatchError
     },
-    compositor::{CompositorHandler, CompositorState},
-    output::{OutputHandler, OutputState},
+    compositor::{CompositorHandler, CompositorState, Region},
+    output::{OutputHandler, OutputState, OutputInfo},
     registry::{ProvidesRegistryState, RegistryState},
     seat::{Capability, SeatHandler, SeatState},
     shell::{
@@ -23,7 +28,7 @@ use smithay_client_toolkit::{
     },
     shm::{
         slot::{Buffer, SlotPool},
-        Shm, ShmHandler,
+        Shm, ShmHandler, CreatePoolError
     },
     delegate_compositor, delegate_output, delegate_registry, delegate_seat,
     delegate_xdg_shell, delegate_layer, delegate_shm, registry_handlers
@@ -31,39 +36,61 @@ use smithay_client_toolkit::{
 use tiny_skia::PixmapMut;
 
 use crate::{
-    ui::Ui,
+    ui::{Ui, Output},
+    theme::Theme,
     widget::bar::Bar,
     geometry::Size
 };
 
+const BAR_SIZE: BarSize = BarSize::Fixed(40);
+/// The layer shell namespace, which compositors can use to
+/// apply rules to the bar surface, e.g. blur.
+const NAMESPACE: &str = "mibar";
+/// How often the bar is redrawn so that widgets showing
+/// live data, e.g. CPU usage, stay up to date.
+const REDRAW_INTERVAL: Duration = Duration::from_secs(1);
+
 fn main() {
-    let conn = Connection::connect_to_env().unwrap();
-    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    #[cfg(feature = "env_logger")]
+    env_logger::init();
+
+    if let Err(err) = run() {
+        // Printed directly since the logger might not be enabled.
+        eprintln!("mibar: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), RunError> {
+    let conn = Connection::connect_to_env().map_err(RunError::Connect)?;
+    let (globals, mut event_queue) = registry_queue_init(&conn)
+        .map_err(RunError::Registry)?;
     let qh = event_queue.handle();
 
     // Initialize xdg_shell handlers so we can select the correct adapter
-    let compositor_state =
-        CompositorState::bind(&globals, &qh).expect("wl_compositor not available");
+    let compositor_state = CompositorState::bind(&globals, &qh)
+        .map_err(|err| RunError::MissingGlobal("wl_compositor", err))?;
 
     let layer_shell = LayerShell::bind(&globals, &qh)
-        .expect("Compositor does not support the zwlr_layer_shell_v1 protocol.");
+        .map_err(|err| RunError::MissingGlobal("zwlr_layer_shell_v1", err))?;
 
     let surface = compositor_state.create_surface(&qh);
 
-    let shm = Shm::bind(&globals, &qh).expect("wl_shm is not available.");
+    let shm = Shm::bind(&globals, &qh)
+        .map_err(|err| RunError::MissingGlobal("wl_shm", err))?;
 
     let layer_surface = layer_shell.create_layer_surface(
         &qh,
         surface,
         Layer::Top,
-        Option::<String>::None,
+        Some(NAMESPACE),
         None
     );
 
-    let pool = SlotPool::new(256 * 256 * 4, &shm)
-        .expect("Failed to create a shared memory pool.");
+    let pool = SlotPool::new(256 * 256 * 4, &shm).map_err(RunError::Pool)?;
 
     let mut bar = Mibar {
+        compositor_state,
         registry_state: RegistryState::new(&globals),
         seat_state: SeatState::new(&globals, &qh),
         output_state: OutputState::new(&globals, &qh),
@@ -73,23 +100,59 @@ fn main() {
         layer_shell,
         layer_surface,
         exit: false,
+        configured: false,
+        output: None,
+        size: BAR_SIZE,
+        // Argb8888 must be supported by all compositors so
+        // it's safe to use until we know what else is available.
+        format: wl_shm::Format::Argb8888,
+        swap_channels: true,
         width: 256,
         height: 256,
         ui: Ui::new(Box::new(Bar::new()))
+            .with_theme(pick_theme(theme::prefers_dark()))
     };
 
+    // The supported formats are sent right after wl_shm is bound.
+    event_queue.roundtrip(&mut bar).map_err(RunError::Dispatch)?;
+
+    let (format, swap_channels) = negotiate_format(bar.shm.formats())
+        .map_err(RunError::Format)?;
+    bar.format = format;
+    bar.swap_channels = swap_channels;
+
+    spawn_redraw_timer(conn.clone(), qh.clone());
+    watch_color_scheme(conn.clone(), qh.clone());
+
     // We don't draw immediately, the configure will notify us when to first draw.
     loop {
-        event_queue.blocking_dispatch(&mut bar).unwrap();
+        event_queue.blocking_dispatch(&mut bar).map_err(RunError::Dispatch)?;
 
         if bar.exit {
-            println!("exiting example");
+            log::info!("Exiting");
             break;
         }
     }
+
+    Ok(())
+}
+
+/// Errors that prevent the bar from starting or keep it from running.
+#[derive(Debug)]
+pub enum RunError {
+    /// Couldn't connect to a Wayland compositor, most likely
+    /// because we are not running inside a Wayland session.
+    Connect(ConnectError),
+    Registry(GlobalError),
+    /// The compositor doesn't provide a global that the bar requires.
+    MissingGlobal(&'static str, BindError),
+    Pool(CreatePoolError),
+    Format(UnsupportedFormatError),
+    Dispatch(DispatchError)
 }
 
 struct Mibar {
+    compositor_state: CompositorState,
     registry_state: RegistryState,
     seat_state: SeatState,
     output_state: OutputState,
@@ -99,11 +162,40 @@ struct Mibar {
     buffer: Option<Buffer>,
     shm: Shm,
     exit: bool,
+    configured: bool,
+    /// The output the bar is sized for and the height requested for it.
+    output: Option<(wl_output::WlOutput, u32)>,
+    size: BarSize,
+    format: wl_shm::Format,
+    /// Whether the red and blue channels have to be swapped after drawing.
+    swap_channels: bool,
     width: u32,
     height: u32,
     ui: Ui
 }
 
+/// User data of the callbacks requested by the redraw timer.
+struct RedrawTick;
+
+/// User data of the callbacks requested when the
+/// color scheme preferred by the desktop changes.
+struct ColorSchemeChanged {
+    prefers_dark: bool
+}
+
+/// None of the pixel formats we know how to draw in are supported
+/// by the compositor. Contains the formats it advertised instead.
+#[derive(Debug)]
+pub struct UnsupportedFormatError(pub Vec<wl_shm::Format>);
+
+/// The height of the bar.
+pub enum BarSize {
+    Fixed(u32),
+    /// Computed from the output the bar is shown on. Called again
+    /// whenever the output changes.
+    PerOutput(fn(&OutputInfo) -> u32)
+}
+
 impl CompositorHandler for Mibar {
     fn scale_factor_changed(
         &mut self,
@@ -132,31 +224,21 @@ impl OutputHandler for Mibar {
     fn new_output(
         &mut self,
         _conn: &Connection,
-        _qh: &QueueHandle<Self>,
+        qh: &QueueHandle<Self>,
         output: wl_output::WlOutput,
     ) {
-        let Some(info) = self.output_state.info(&output) else {
-            return;
-        };
-
-        let Some(size) = info.logical_size else {
-            return;
-        };
-        println!("New output: {:?}", info);
-
-        self.layer_surface.set_anchor(Anchor::BOTTOM);
-        self.layer_surface.set_size(size.0 as u32, 40);
-        self.layer_surface.set_exclusive_zone(40);
-        self.layer_surface.commit();
+        log::debug!("New output: {:?}", output);
+        self.output_changed(qh, &output);
     }
 
     fn update_output(
         &mut self,
         _conn: &Connection,
-        _qh: &QueueHandle<Self>,
+        qh: &QueueHandle<Self>,
         output: wl_output::WlOutput,
     ) {
-        println!("Update output: {:?}", output);
+        log::debug!("Update output: {:?}", output);
+        self.output_changed(qh, &output);
     }
 
     fn output_destroyed(
@@ -165,7 +247,11 @@ impl OutputHandler for Mibar {
         _qh: &QueueHandle<Self>,
         output: wl_output::WlOutput,
     ) {
-        println!("Output destroyed: {:?}", output);
+        log::debug!("Output destroyed: {:?}", output);
+
+        if self.output.as_ref().is_some_and(|(current, _)| *current == output) {
+            self.output = None;
+        }
     }
 }
 
@@ -203,6 +289,44 @@ impl ShmHandler for Mibar {
     }
 }
 
+impl Dispatch<wl_callback::WlCallback, RedrawTick> for Mibar {
+    fn event(
+        state: &mut Self,
+        _callback: &wl_callback::WlCallback,
+        _event: wl_callback::Event,
+        _data: &RedrawTick,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if state.configured {
+            state.draw(qh);
+        }
+    }
+}
+
+impl Dispatch<wl_callback::WlCallback, ColorSchemeChanged> for Mibar {
+    fn event(
+        state: &mut Self,
+        _callback: &wl_callback::WlCallback,
+        _event: wl_callback::Event,
+        data: &ColorSchemeChanged,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        log::debug!("Color scheme changed, prefers dark: {}", data.prefers_dark);
+
+        state.ui.set_theme(pick_theme(data.prefers_dark));
+
+        // The clear color of the new theme might not be opaque.
+        state.update_opaque_region();
+
+        if state.configured {
+            state.layout();
+            state.draw(qh);
+        }
+    }
+}
+
 impl ProvidesRegistryState for Mibar {
     fn registry(&mut self) -> &mut RegistryState {
         &mut self.registry_state
@@ -228,56 +352,196 @@ impl LayerShellHandler for Mibar {
         configure: LayerSurfaceConfigure,
         _serial: u32,
     ) {
-        println!("layer draw size: {:?}", configure.new_size);
-        self.width = configure.new_size.0;
-        self.height = configure.new_size.1;
+        log::debug!("Layer draw size: {:?}", configure.new_size);
+
+        let ((width, height), resized) = configured_size(
+            (self.width, self.height),
+            configure.new_size
+        );
 
+        // The buffer is sized for the previous dimensions, so it
+        // has to be reallocated whether the surface grew or shrank.
+        if resized {
+            self.buffer = None;
+        }
+
+        self.width = width;
+        self.height = height;
+        self.configured = true;
+        self.update_opaque_region();
+
+        self.layout();
+        self.draw(qh);
+    }
+}
+
+impl Mibar {
+    fn output_changed(&mut self, qh: &QueueHandle<Self>, output: &wl_output::WlOutput) {
+        // The surface is created without an output, so we follow the first
+        // one that becomes usable. Changes to any other output don't concern us.
+        if self.output.as_ref().is_some_and(|(current, _)| current != output) {
+            return;
+        }
+
+        let Some(info) = self.output_state.info(output) else {
+            return;
+        };
+
+        // The logical size is not always known when the output is first
+        // announced. In that case we wait for the update that carries it.
+        let Some(size) = info.logical_size else {
+            return;
+        };
+
+        let pixel_size = info.modes.iter()
+            .find(|mode| mode.current)
+            .map_or(Size::ZERO, |mode| Size::new(
+                mode.dimensions.0 as f32,
+                mode.dimensions.1 as f32
+            ));
+
+        let new = Output {
+            logical_size: Size::new(size.0 as f32, size.1 as f32),
+            scale_factor: info.scale_factor,
+            pixel_size,
+            physical_size: Size::new(
+                info.physical_size.0 as f32,
+                info.physical_size.1 as f32
+            )
+        };
+        let height = self.size.resolve(&info);
+
+        // Updates are also sent for properties we don't use, e.g. the name.
+        let unchanged = self.output.as_ref().is_some_and(|(_, current)| *current == height) &&
+            self.ui.output() == Some(&new);
+
+        if unchanged {
+            return;
+        }
+
+        self.output = Some((output.clone(), height));
+        self.ui.set_output(new);
+
+        self.layer_surface.set_anchor(Anchor::BOTTOM);
+        self.layer_surface.set_size(size.0 as u32, height);
+        self.layer_surface.set_exclusive_zone(height as i32);
+        self.layer_surface.commit();
+
+        // If the surface was configured before the output became usable
+        // nothing else is going to trigger a draw, so paint the current
+        // frame ourselves. A size change will still arrive via configure.
+        // The widgets might lay themselves out differently for the new
+        // output, so do that again too.
+        if self.configured {
+            self.layout();
+            self.draw(qh);
+        }
+    }
+
+    #[inline]
+    fn layout(&mut self) {
         self.ui.layout(Size {
             width: self.width as f32,
             height: self.height as f32
         });
-        self.draw(qh);
     }
-}
 
-impl Mibar {
+    /// Lets the compositor skip blending whatever is behind
+    /// the bar when we are going to cover it completely anyway.
+    fn update_opaque_region(&self) {
+        let surface = self.layer_surface.wl_surface();
+
+        if !self.ui.clear_color().is_opaque() {
+            surface.set_opaque_region(None);
+
+            return;
+        }
+
+        let Ok(region) = Region::new(&self.compositor_state) else {
+            return;
+        };
+
+        region.add(0, 0, self.width as i32, self.height as i32);
+        surface.set_opaque_region(Some(region.wl_region()));
+    }
+
     fn draw(&mut self, qh: &QueueHandle<Self>) {
-        println!("redraw");
+        log::trace!("Redraw");
 
         let width = self.width;
         let height = self.height;
         let stride = self.width as i32 * 4;
 
-        let format = wl_shm::Format::Argb8888;
+        let format = self.format;
 
-        let buffer = self.buffer.get_or_insert_with(|| {
-            self.pool
-                .create_buffer(width as i32, height as i32, stride, format)
-                .expect("create buffer")
-                .0
-        });
+        // The pool is created with a small initial size. Make sure a buffer
+        // for the actual surface size fits, which on large outputs is much bigger.
+        let len = stride as usize * height as usize;
+
+        if self.pool.len() < len {
+            if let Err(err) = self.pool.resize(len) {
+                log::error!("Failed to resize the shared memory pool: {}", err);
+
+                return;
+            }
+        }
+
+        let buffer = match &mut self.buffer {
+            Some(buffer) => buffer,
+            None => match self.pool.create_buffer(
+                width as i32,
+                height as i32,
+                stride,
+                format
+            ) {
+                Ok((buffer, _)) => self.buffer.insert(buffer),
+                Err(err) => {
+                    log::error!("Failed to create a buffer: {}", err);
+
+                    return;
+                }
+            }
+        };
 
         let canvas = match self.pool.canvas(buffer) {
             Some(canvas) => canvas,
             None => {
                 // This should be rare, but if the compositor has not released the previous
                 // buffer, we need double-buffering.
-                let (second_buffer, canvas) = self.pool
-                    .create_buffer(
-                        width as i32,
-                        height as i32,
-                        stride,
-                        format
-                    ).expect("create buffer");
-
-                *buffer = second_buffer;
-
-                canvas
+                let result = self.pool.create_buffer(
+                    width as i32,
+                    height as i32,
+                    stride,
+                    format
+                );
+
+                match result {
+                    Ok((second_buffer, canvas)) => {
+                        *buffer = second_buffer;
+
+                        canvas
+                    }
+                    Err(err) => {
+                        log::error!("Failed to create a buffer: {}", err);
+
+                        return;
+                    }
+                }
             }
         };
 
-        let mut pixmap = PixmapMut::from_bytes(canvas, width, height).unwrap();
-        self.ui.draw(&mut pixmap);        
+        self.ui.update();
+
+        {
+            let mut pixmap = PixmapMut::from_bytes(&mut *canvas, width, height).unwrap();
+            self.ui.draw(&mut pixmap);        
+        }
+
+        if self.swap_channels {
+            for pixel in canvas.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
 
         let surface = self.layer_surface.wl_surface();
         // Damage the entire window
@@ -293,6 +557,133 @@ impl Mibar {
     }
 }
 
+/// Wakes up the event loop every [`REDRAW_INTERVAL`] to redraw the bar.
+/// The compositor answers each `wl_display.sync` with a done event which
+/// is then dispatched on our queue like any other event.
+fn spawn_redraw_timer(conn: Connection, qh: QueueHandle<Mibar>) {
+    thread::spawn(move || loop {
+        thread::sleep(REDRAW_INTERVAL);
+
+        conn.display().sync(&qh, RedrawTick);
+
+        // The connection is gone, the main loop will report why.
+        if conn.flush().is_err() {
+            break;
+        }
+    });
+}
+
+/// Switches the theme whenever the color scheme preferred by the desktop
+/// changes. Like the redraw timer, the change is sent through the compositor
+/// so that it's handled on the main loop.
+fn watch_color_scheme(conn: Connection, qh: QueueHandle<Mibar>) {
+    theme::watch_color_scheme(move |prefers_dark| {
+        conn.display().sync(&qh, ColorSchemeChanged { prefers_dark });
+
+        // The connection is gone, the main loop will report why.
+        let _ = conn.flush();
+    });
+}
+
+#[inline]
+fn pick_theme(prefers_dark: bool) -> Theme {
+    if prefers_dark {
+        Theme::dark()
+    } else {
+        Theme::light()
+    }
+}
+
+/// Applies the size from a configure event to the `current` size of the surface.
+/// A zero dimension means that we get to pick it, so the current one is kept.
+/// Returns the resulting size and whether it differs from the current one.
+fn configured_size(current: (u32, u32), new: (u32, u32)) -> ((u32, u32), bool) {
+    let width = if new.0 == 0 { current.0 } else { new.0 };
+    let height = if new.1 == 0 { current.1 } else { new.1 };
+
+    ((width, height), (width, height) != current)
+}
+
+/// Picks the format of our shared memory buffers from the ones
+/// supported by the compositor. tiny-skia draws RGBA bytes which is
+/// what `Abgr8888` is since Wayland formats are little endian.
+/// `Argb8888` must be supported by all compositors but requires the
+/// red and blue channels to be swapped after drawing, which is
+/// indicated by the returned `bool`.
+fn negotiate_format(
+    formats: &[wl_shm::Format]
+) -> Result<(wl_shm::Format, bool), UnsupportedFormatError> {
+    if formats.contains(&wl_shm::Format::Abgr8888) {
+        Ok((wl_shm::Format::Abgr8888, false))
+    } else if formats.contains(&wl_shm::Format::Argb8888) {
+        Ok((wl_shm::Format::Argb8888, true))
+    } else {
+        Err(UnsupportedFormatError(formats.to_vec()))
+    }
+}
+
+impl fmt::Display for UnsupportedFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Compositor supports neither Abgr8888 nor Argb8888 shm formats. Available formats: {:?}",
+            self.0
+        )
+    }
+}
+
+impl Error for UnsupportedFormatError { }
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connect(err) => write!(
+                f,
+                "Failed to connect to a Wayland compositor, is WAYLAND_DISPLAY set? ({})",
+                err
+            ),
+            Self::Registry(err) => write!(f, "Failed to initialize the Wayland registry: {}", err),
+            Self::MissingGlobal(name, err) => write!(f, "{} is not available: {}", name, err),
+            Self::Pool(err) => write!(f, "Failed to create a shared memory pool: {}", err),
+            Self::Format(err) => write!(f, "{}", err),
+            Self::Dispatch(err) => write!(f, "Failed to dispatch Wayland events: {}", err)
+        }
+    }
+}
+
+impl Error for RunError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Connect(err) => Some(err),
+            Self::Registry(err) => Some(err),
+            Self::MissingGlobal(_, err) => Some(err),
+            Self::Pool(err) => Some(err),
+            Self::Format(err) => Some(err),
+            Self::Dispatch(err) => Some(err)
+        }
+    }
+}
+
+impl BarSize {
+    /// The height of the bar on the given output. Never zero since the
+    /// surface is only anchored to one edge, in which case the compositor
+    /// treats a zero height as a protocol error.
+    pub fn resolve(&self, info: &OutputInfo) -> u32 {
+        let size = match self {
+            Self::Fixed(size) => *size,
+            Self::PerOutput(func) => func(info)
+        };
+
+        if size == 0 {
+            log::error!("The bar height resolved to 0, using 1 instead");
+
+            return 1;
+        }
+
+        size
+    }
+}
+
 delegate_compositor!(Mibar);
 delegate_output!(Mibar);
 
@@ -304,3 +695,33 @@ delegate_shm!(Mibar);
 delegate_registry!(Mibar);
 
 delegate_layer!(Mibar);
+
+#[cfg(test)]
+mod tests {
+    use super::configured_size;
+
+    #[test]
+    fn configure_grow() {
+        assert_eq!(configured_size((1280, 40), (1920, 40)), ((1920, 40), true));
+        assert_eq!(configured_size((1920, 40), (1920, 60)), ((1920, 60), true));
+    }
+
+    #[test]
+    fn configure_shrink() {
+        assert_eq!(configured_size((1920, 40), (1280, 40)), ((1280, 40), true));
+        assert_eq!(configured_size((1920, 60), (1920, 40)), ((1920, 40), true));
+    }
+
+    #[test]
+    fn configure_equal() {
+        assert_eq!(configured_size((1920, 40), (1920, 40)), ((1920, 40), false));
+    }
+
+    #[test]
+    fn configure_zero_dimension() {
+        assert_eq!(configured_size((1920, 40), (0, 40)), ((1920, 40), false));
+        assert_eq!(configured_size((1920, 40), (0, 60)), ((1920, 60), true));
+        assert_eq!(configured_size((1920, 40), (1280, 0)), ((1280, 40), true));
+        assert_eq!(configured_size((1920, 40), (0, 0)), ((1920, 40), false));
+    }
+}
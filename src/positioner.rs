@@ -9,12 +9,7 @@ impl Positioner {
     #[inline]
     pub fn new(size: Size) -> Self {
         Self {
-            bounds: Rect {
-                x: 0f32,
-                y: 0f32,
-                width: size.width,
-                height: size.height
-            } 
+            bounds: Rect::from_size(size)
         }
     }
 
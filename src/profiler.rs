@@ -0,0 +1,47 @@
+use std::time::{Duration, Instant};
+
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Accumulates draw timings and logs a summary
+/// at most once every `REPORT_INTERVAL`.
+pub struct Profiler {
+    frames: u32,
+    draw_time: Duration,
+    commands: usize,
+    last_report: Instant
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self {
+            frames: 0,
+            draw_time: Duration::ZERO,
+            commands: 0,
+            last_report: Instant::now()
+        }
+    }
+
+    /// Records a single frame which took `draw_time` to
+    /// draw and issued `commands` draw calls.
+    pub fn record(&mut self, draw_time: Duration, commands: usize) {
+        self.frames += 1;
+        self.draw_time += draw_time;
+        self.commands += commands;
+
+        let elapsed = self.last_report.elapsed();
+
+        if elapsed < REPORT_INTERVAL {
+            return;
+        }
+
+        log::info!(
+            "{} frame(s) in {:.2?}: {:.2?} average draw time, {} draw commands per frame",
+            self.frames,
+            elapsed,
+            self.draw_time / self.frames,
+            self.commands / self.frames as usize
+        );
+
+        *self = Self::new();
+    }
+}
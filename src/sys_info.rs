@@ -0,0 +1,330 @@
+use std::{fs, io};
+
+const MEMINFO: &str = "/proc/meminfo";
+const STAT: &str = "/proc/stat";
+
+/// Memory statistics in bytes as reported by `/proc/meminfo`.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct RamUsage {
+    pub total: u64,
+    pub available: u64,
+    pub free: u64,
+    pub buffers: u64,
+    pub cached: u64,
+    pub swap_total: u64,
+    pub swap_free: u64
+}
+
+/// Time spent by a CPU since boot, in clock ticks.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct CpuTimes {
+    pub busy: u64,
+    pub total: u64
+}
+
+/// A snapshot of the CPU times reported by `/proc/stat`. Usage is
+/// computed from the difference between two snapshots.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct CpuStat {
+    pub aggregate: CpuTimes,
+    pub cores: Vec<CpuTimes>
+}
+
+/// CPU usage in percent between two [`CpuStat`] snapshots.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct CpuUsage {
+    pub total: f64,
+    pub cores: Vec<f64>
+}
+
+impl RamUsage {
+    #[inline]
+    pub fn read() -> io::Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(MEMINFO)?))
+    }
+
+    /// Parses the contents of `/proc/meminfo`. Missing
+    /// or malformed entries are left as zero.
+    pub fn parse(meminfo: &str) -> Self {
+        let mut usage = Self::default();
+
+        for line in meminfo.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+
+            // Values are always reported in kibibytes.
+            let Some(value) = value
+                .split_whitespace()
+                .next()
+                .and_then(|x| x.parse::<u64>().ok()) else {
+                continue;
+            };
+
+            let field = match key {
+                "MemTotal" => &mut usage.total,
+                "MemAvailable" => &mut usage.available,
+                "MemFree" => &mut usage.free,
+                "Buffers" => &mut usage.buffers,
+                "Cached" => &mut usage.cached,
+                "SwapTotal" => &mut usage.swap_total,
+                "SwapFree" => &mut usage.swap_free,
+                _ => continue
+            };
+
+            *field = value * 1024;
+        }
+
+        usage
+    }
+
+    /// Memory that is not available for starting new applications.
+    #[inline]
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+
+    #[inline]
+    pub fn used_percentage(&self) -> f64 {
+        percentage(self.used(), self.total)
+    }
+
+    /// Memory used by the page cache and buffers. Most of it
+    /// can be reclaimed by the kernel when needed.
+    #[inline]
+    pub fn cache(&self) -> u64 {
+        self.buffers + self.cached
+    }
+
+    #[inline]
+    pub fn swap_used(&self) -> u64 {
+        self.swap_total.saturating_sub(self.swap_free)
+    }
+
+    #[inline]
+    pub fn swap_percentage(&self) -> f64 {
+        percentage(self.swap_used(), self.swap_total)
+    }
+}
+
+impl CpuStat {
+    #[inline]
+    pub fn read() -> io::Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(STAT)?))
+    }
+
+    /// Parses the `cpu` and `cpuN` lines of `/proc/stat`.
+    pub fn parse(stat: &str) -> Self {
+        let mut result = Self::default();
+
+        for line in stat.lines() {
+            let mut fields = line.split_whitespace();
+
+            let Some(name) = fields.next() else {
+                continue;
+            };
+
+            let Some(core) = name.strip_prefix("cpu") else {
+                continue;
+            };
+
+            // user, nice, system, idle, iowait, irq, softirq, steal.
+            // The guest times that follow are already included in user and nice.
+            let mut values = [0u64; 8];
+
+            for (value, field) in values.iter_mut().zip(fields) {
+                *value = field.parse().unwrap_or(0);
+            }
+
+            let total: u64 = values.iter().sum();
+            let idle = values[3] + values[4];
+            let times = CpuTimes {
+                busy: total - idle,
+                total
+            };
+
+            if core.is_empty() {
+                result.aggregate = times;
+            } else {
+                result.cores.push(times);
+            }
+        }
+
+        result
+    }
+
+    /// The usage since the `previous` snapshot. If the number of
+    /// cores changed in between, missing cores are compared to zero.
+    pub fn usage(&self, previous: &Self) -> CpuUsage {
+        let cores = self.cores.iter().enumerate().map(|(i, times)| {
+            let previous = previous.cores.get(i).copied().unwrap_or_default();
+
+            times.usage(&previous)
+        }).collect();
+
+        CpuUsage {
+            total: self.aggregate.usage(&previous.aggregate),
+            cores
+        }
+    }
+}
+
+impl CpuTimes {
+    #[inline]
+    pub fn usage(&self, previous: &Self) -> f64 {
+        percentage(
+            self.busy.saturating_sub(previous.busy),
+            self.total.saturating_sub(previous.total)
+        )
+    }
+}
+
+#[inline]
+fn percentage(value: u64, total: u64) -> f64 {
+    if total == 0 {
+        return 0f64;
+    }
+
+    (value as f64 / total as f64) * 100f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KIB: u64 = 1024;
+
+    const MEMINFO: &str = "\
+MemTotal:       16000000 kB
+MemFree:         2000000 kB
+MemAvailable:    8000000 kB
+Buffers:          500000 kB
+Cached:          3000000 kB
+SwapCached:            0 kB
+SwapTotal:       4000000 kB
+SwapFree:        3000000 kB
+HugePages_Total:       0
+";
+
+    #[test]
+    fn parse_meminfo() {
+        let usage = RamUsage::parse(MEMINFO);
+
+        assert_eq!(usage, RamUsage {
+            total: 16000000 * KIB,
+            available: 8000000 * KIB,
+            free: 2000000 * KIB,
+            buffers: 500000 * KIB,
+            cached: 3000000 * KIB,
+            swap_total: 4000000 * KIB,
+            swap_free: 3000000 * KIB
+        });
+
+        assert_eq!(usage.used(), 8000000 * KIB);
+        assert_eq!(usage.used_percentage(), 50f64);
+        assert_eq!(usage.cache(), 3500000 * KIB);
+        assert_eq!(usage.swap_used(), 1000000 * KIB);
+        assert_eq!(usage.swap_percentage(), 25f64);
+    }
+
+    #[test]
+    fn parse_incomplete_meminfo() {
+        let usage = RamUsage::parse("\
+MemTotal:       16000000 kB
+MemAvailable:   abc kB
+Buffers
+Cached:
+SwapTotal 4000000 kB
+");
+
+        assert_eq!(usage, RamUsage {
+            total: 16000000 * KIB,
+            ..RamUsage::default()
+        });
+
+        // Nothing is available, so everything counts as used.
+        assert_eq!(usage.used_percentage(), 100f64);
+    }
+
+    #[test]
+    fn empty_meminfo() {
+        let usage = RamUsage::parse("");
+
+        assert_eq!(usage, RamUsage::default());
+        assert_eq!(usage.used_percentage(), 0f64);
+        assert_eq!(usage.swap_percentage(), 0f64);
+    }
+
+    const STAT: &str = "\
+cpu  100 0 50 800 50 0 0 0 0 0
+cpu0 60 0 20 400 20 0 0 0 0 0
+cpu1 40 0 30 400 30 0 0 0
+cpu2 10 5
+intr 12345 0 1 2
+ctxt 67890
+btime 1700000000
+";
+
+    #[test]
+    fn parse_cpu_stat() {
+        let stat = CpuStat::parse(STAT);
+
+        // Idle time is idle + iowait.
+        assert_eq!(stat.aggregate, CpuTimes { busy: 150, total: 1000 });
+        assert_eq!(stat.cores, [
+            CpuTimes { busy: 80, total: 500 },
+            CpuTimes { busy: 70, total: 500 },
+            // Missing fields count as zero.
+            CpuTimes { busy: 15, total: 15 }
+        ]);
+    }
+
+    #[test]
+    fn parse_empty_cpu_stat() {
+        assert_eq!(CpuStat::parse(""), CpuStat::default());
+        assert_eq!(CpuStat::parse("intr 1 2 3\nctxt 4"), CpuStat::default());
+    }
+
+    #[test]
+    fn cpu_usage() {
+        let previous = CpuStat::parse(STAT);
+        let current = CpuStat::parse("\
+cpu  150 0 100 1100 150 0 0 0 0 0
+cpu0 100 0 20 500 30 0 0 0 0 0
+cpu1 40 0 30 400 30 0 0 0
+cpu2 10 5
+");
+
+        let usage = current.usage(&previous);
+
+        // 100 busy out of 500 ticks.
+        assert_eq!(usage.total, 20f64);
+        // 40 busy out of 150 ticks. No time passed for the other cores.
+        assert!((usage.cores[0] - 40f64 / 150f64 * 100f64).abs() < 1e-9);
+        assert_eq!(usage.cores[1..], [0f64, 0f64]);
+    }
+
+    #[test]
+    fn cpu_usage_with_changed_core_count() {
+        let previous = CpuStat::parse("\
+cpu  100 0 0 100 0 0 0 0
+cpu0 100 0 0 100 0 0 0 0
+");
+        let current = CpuStat::parse("\
+cpu  200 0 0 300 0 0 0 0
+cpu0 150 0 0 150 0 0 0 0
+cpu1 50 0 0 150 0 0 0 0
+");
+
+        let usage = current.usage(&previous);
+
+        assert_eq!(usage.total, (100f64 / 300f64) * 100f64);
+        assert_eq!(usage.cores.len(), 2);
+        assert_eq!(usage.cores[0], 50f64);
+        // The new core is compared against zero i.e. its times since boot.
+        assert_eq!(usage.cores[1], 25f64);
+
+        // Cores that went away are not reported.
+        assert_eq!(previous.usage(&current).cores.len(), 1);
+    }
+}
@@ -1,3 +1,6 @@
+#[cfg(feature = "portal")]
+use std::thread;
+
 use tiny_skia::Color;
 
 pub struct Theme {
@@ -33,4 +36,169 @@ impl Theme {
             cold3: Color::from_rgba8(144, 122, 169, 255)
         }
     }
+
+    #[inline]
+    pub fn dark() -> Self {
+        Self {
+            base: Color::from_rgba8(25, 23, 36, 255),
+            surface: Color::from_rgba8(31, 29, 46, 255),
+            overlay: Color::from_rgba8(38, 35, 58, 255),
+            muted: Color::from_rgba8(110, 106, 134, 255),
+            subtle: Color::from_rgba8(144, 140, 170, 255),
+            text: Color::from_rgba8(224, 222, 244, 255),
+            warm1: Color::from_rgba8(235, 111, 146, 255),
+            warm2: Color::from_rgba8(246, 193, 119, 255),
+            warm3: Color::from_rgba8(235, 188, 186, 255),
+            cold1: Color::from_rgba8(49, 116, 143, 255),
+            cold2: Color::from_rgba8(156, 207, 216, 255),
+            cold3: Color::from_rgba8(196, 167, 231, 255)
+        }
+    }
+
+    // Semantic names for the palette so that widgets can pick
+    // colors by purpose rather than by hue.
+
+    #[inline]
+    pub fn background(&self) -> Color {
+        self.base
+    }
+
+    #[inline]
+    pub fn primary(&self) -> Color {
+        self.cold1
+    }
+
+    #[inline]
+    pub fn secondary(&self) -> Color {
+        self.cold3
+    }
+
+    #[inline]
+    pub fn accent(&self) -> Color {
+        self.warm3
+    }
+
+    #[inline]
+    pub fn success(&self) -> Color {
+        self.cold2
+    }
+
+    #[inline]
+    pub fn warning(&self) -> Color {
+        self.warm2
+    }
+
+    #[inline]
+    pub fn danger(&self) -> Color {
+        self.warm1
+    }
+}
+
+/// Whether the desktop prefers a dark color scheme, read from the `color-scheme`
+/// setting of the XDG desktop portal. False if there's no preference, it can't
+/// be determined or the `portal` feature is disabled.
+/// See https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.Settings.html
+#[cfg(feature = "portal")]
+pub fn prefers_dark() -> bool {
+    use zbus::{blocking::connection::Builder, zvariant::OwnedValue};
+
+    let read = || -> zbus::Result<bool> {
+        // This runs on startup, so a stuck portal must not hold up the bar.
+        let conn = Builder::session()?
+            .method_timeout(portal::TIMEOUT)
+            .build()?;
+
+        let value: OwnedValue = portal::settings(&conn)?
+            .call("Read", &(portal::APPEARANCE, portal::COLOR_SCHEME))?;
+
+        portal::is_dark(&value)
+    };
+
+    match read() {
+        Ok(dark) => dark,
+        Err(err) => {
+            log::warn!("Failed to read the preferred color scheme: {}", err);
+
+            false
+        }
+    }
+}
+
+#[cfg(not(feature = "portal"))]
+#[inline]
+pub fn prefers_dark() -> bool {
+    false
+}
+
+/// Calls `f` with the result of [`prefers_dark`] every time the color scheme
+/// preferred by the desktop changes. Changes are received on a separate thread.
+/// Does nothing if the `portal` feature is disabled.
+#[cfg(feature = "portal")]
+pub fn watch_color_scheme(mut f: impl FnMut(bool) + Send + 'static) {
+    thread::spawn(move || {
+        if let Err(err) = portal::watch(&mut f) {
+            log::warn!("Stopped following the preferred color scheme: {}", err);
+        }
+    });
+}
+
+#[cfg(not(feature = "portal"))]
+#[inline]
+pub fn watch_color_scheme(_f: impl FnMut(bool) + Send + 'static) { }
+
+#[cfg(feature = "portal")]
+mod portal {
+    use std::time::Duration;
+
+    use zbus::{
+        blocking::{Connection, Proxy},
+        zvariant::{OwnedValue, Value}
+    };
+
+    pub const TIMEOUT: Duration = Duration::from_millis(500);
+    pub const APPEARANCE: &str = "org.freedesktop.appearance";
+    pub const COLOR_SCHEME: &str = "color-scheme";
+
+    const PREFER_DARK: u32 = 1;
+
+    #[inline]
+    pub fn settings(conn: &Connection) -> zbus::Result<Proxy<'static>> {
+        Proxy::new(
+            conn,
+            "org.freedesktop.portal.Desktop",
+            "/org/freedesktop/portal/desktop",
+            "org.freedesktop.portal.Settings"
+        )
+    }
+
+    pub fn watch(f: &mut impl FnMut(bool)) -> zbus::Result<()> {
+        let conn = Connection::session()?;
+        let settings = settings(&conn)?;
+
+        for message in settings.receive_signal("SettingChanged")? {
+            let (namespace, key, value): (String, String, OwnedValue) =
+                message.body().deserialize()?;
+
+            if namespace != APPEARANCE || key != COLOR_SCHEME {
+                continue;
+            }
+
+            match is_dark(&value) {
+                Ok(dark) => f(dark),
+                Err(err) => log::warn!("Invalid color scheme preference: {}", err)
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn is_dark(value: &Value) -> zbus::Result<bool> {
+        // Read returns the setting wrapped in an extra variant.
+        let scheme = match value {
+            Value::Value(inner) => u32::try_from(&**inner),
+            value => u32::try_from(value)
+        }?;
+
+        Ok(scheme == PREFER_DARK)
+    }
 }
@@ -1,11 +1,15 @@
-use std::mem;
+use std::{mem, env};
+#[cfg(feature = "profiling")]
+use std::time::Instant;
 
 use tiny_skia::{
     PixmapMut, PathBuilder, FillRule, Transform,
-    Paint, Color, LinearGradient, Shader
+    Paint, Color, LinearGradient, Shader, Stroke, LineJoin, LineCap
 };
+#[cfg(test)]
+use tiny_skia::Pixmap;
 use crate::{
-    geometry::{Rect, Circle, Size},
+    geometry::{Rect, Circle, Size, Point, BorderRadius},
     positioner::Positioner,
     widget::{
         Widget,
@@ -13,54 +17,205 @@ use crate::{
     },
     theme::Theme
 };
+#[cfg(feature = "profiling")]
+use crate::profiler::Profiler;
+
+/// Set this environment variable to outline the bounds
+/// of every widget and print the widget tree on each draw.
+const DEBUG_LAYOUT_VAR: &str = "MIBAR_DEBUG_LAYOUT";
 
 pub struct Ui {
     theme: Theme,
     root: Box<dyn Widget>,
-    size: Size
+    size: Size,
+    output: Option<Output>,
+    clear_color: Option<Color>,
+    debug_layout: bool,
+    #[cfg(feature = "profiling")]
+    profiler: Profiler
 }
 
 pub struct DrawCtx<'a> {
     pub theme: &'a Theme,
+    /// The output the bar is sized for, if it's known yet.
+    pub output: Option<&'a Output>,
     pixmap: &'a mut PixmapMut<'a>,
-    builder: PathBuilder
+    builder: PathBuilder,
+    #[cfg(feature = "profiling")]
+    commands: usize
+}
+
+/// Information available to widgets while they are laid out.
+pub struct LayoutCtx<'a> {
+    pub theme: &'a Theme,
+    /// The output the bar is sized for, if it's known yet. Can be used
+    /// to e.g. pick a more compact layout on narrow screens.
+    pub output: Option<&'a Output>
 }
 
+/// Describes the output (monitor) the bar is sized for. The compositor
+/// decides where the surface is shown, which is normally this output.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Output {
+    /// Size in surface-local coordinates i.e with the scale factor
+    /// and transform applied.
+    pub logical_size: Size,
+    pub scale_factor: i32,
+    /// Size of the current mode in pixels, before the transform is
+    /// applied. Zero if the output didn't report a current mode.
+    pub pixel_size: Size,
+    /// Physical size in millimeters, before the transform is applied.
+    /// Can be zero if the size doesn't make sense for the output
+    /// e.g. projectors.
+    pub physical_size: Size
+}
+
+#[derive(Clone, Debug)]
 pub enum Background {
     Color(Color),
     LinearGradient(LinearGradient)
 }
 
+#[derive(Clone, Debug)]
+pub struct QuadStyle {
+    pub background: Background,
+    pub border_radius: BorderRadius,
+    pub border_width: f32,
+    pub border_color: Background
+}
+
 impl Ui {
     pub fn new(root: Box<dyn Widget>) -> Self {
         Self {
             root,
             theme: Theme::light(),   
-            size: Size::ZERO
+            size: Size::ZERO,
+            output: None,
+            clear_color: None,
+            debug_layout: env::var_os(DEBUG_LAYOUT_VAR).is_some(),
+            #[cfg(feature = "profiling")]
+            profiler: Profiler::new()
         }
     }
 
+    #[inline]
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+
+        self
+    }
+
+    /// The new theme is used the next time the UI is laid out and drawn.
+    #[inline]
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// The color the surface is filled with before drawing
+    /// the widgets. Defaults to the background color of the theme.
+    #[inline]
+    pub fn with_clear_color(mut self, color: Color) -> Self {
+        self.clear_color = Some(color);
+
+        self
+    }
+
+    #[inline]
+    pub fn clear_color(&self) -> Color {
+        self.clear_color.unwrap_or(self.theme.background())
+    }
+
+    #[inline]
+    pub fn output(&self) -> Option<&Output> {
+        self.output.as_ref()
+    }
+
+    /// The new output is visible to the widgets the next time
+    /// the UI is laid out and drawn.
+    #[inline]
+    pub fn set_output(&mut self, output: Output) {
+        self.output = Some(output);
+    }
+
+    /// Lets the widgets refresh their data before the next draw.
+    #[inline]
+    pub fn update(&mut self) {
+        self.root.update();
+    }
+
     pub fn layout(&mut self, size: Size) {
         self.size = size;
-        self.root.layout(SizeConstraints::tight(size));
+        let ctx = LayoutCtx {
+            theme: &self.theme,
+            output: self.output.as_ref()
+        };
+
+        self.root.layout(&ctx, SizeConstraints::tight(size));
     }
 
     pub fn draw<'a: 'b, 'b>(&'a mut self, pixmap: &'b mut PixmapMut<'b>) {
         assert_eq!(pixmap.width() , self.size.width as u32);
         assert_eq!(pixmap.height() , self.size.height as u32);
 
-        pixmap.fill(self.theme.base);
+        #[cfg(feature = "profiling")]
+        let start = Instant::now();
+
+        pixmap.fill(self.clear_color());
 
         let mut ctx = DrawCtx {
             theme: &self.theme,
+            output: self.output.as_ref(),
             pixmap,
-            builder: PathBuilder::new()
+            builder: PathBuilder::new(),
+            #[cfg(feature = "profiling")]
+            commands: 0
         };
 
         self.root.draw(&mut ctx, Positioner::new(self.size));
+
+        if self.debug_layout {
+            debug_layout(&mut ctx, self.root.as_mut(), Positioner::new(self.size), 0);
+        }
+
+        #[cfg(feature = "profiling")]
+        self.profiler.record(start.elapsed(), ctx.commands);
+    }
+
+    /// Lays out and draws the widget tree into a new pixmap of the given
+    /// size without involving Wayland. Only meant for tests since it changes
+    /// the size the widgets are laid out at. Returns `None` if either
+    /// dimension is zero.
+    #[cfg(test)]
+    pub fn render_to_pixmap(&mut self, size: Size) -> Option<Pixmap> {
+        let size = size.expand();
+        let mut pixmap = Pixmap::new(size.width as u32, size.height as u32)?;
+
+        self.layout(size);
+        self.draw(&mut pixmap.as_mut());
+
+        Some(pixmap)
     }
 }
 
+fn debug_layout(
+    ctx: &mut DrawCtx,
+    widget: &mut dyn Widget,
+    positioner: Positioner,
+    depth: usize
+) {
+    let bounds = positioner.bounds;
+    log::info!("{:indent$}{:?}", "", bounds, indent = depth * 2);
+
+    // The outline is drawn inside the bounds so there must be room for it.
+    if bounds.width > 1f32 && bounds.height > 1f32 {
+        ctx.stroke_rect(bounds, 1f32, Color::from_rgba8(255, 0, 0, 255));
+    }
+
+    widget.children(&mut |child, rect| {
+        debug_layout(ctx, child, positioner.next(rect), depth + 1);
+    });
+}
+
 impl<'a> DrawCtx<'a> {
     #[inline]
     pub fn fill_circle(&mut self, circle: Circle, bg: impl Into<Background>) {
@@ -75,22 +230,172 @@ impl<'a> DrawCtx<'a> {
         self.draw_path(bg);
     }
 
+    /// Draws the outline of `rect` with the stroke fully contained inside it.
+    #[inline]
+    pub fn stroke_rect(
+        &mut self,
+        rect: Rect,
+        width: f32,
+        bg: impl Into<Background>
+    ) {
+        let half = width / 2f32;
+        self.builder.push_rect(
+            rect.x + half,
+            rect.y + half,
+            rect.width - width,
+            rect.height - width
+        );
+        self.draw_stroke(bg, &Stroke { width, ..Stroke::default() });
+    }
+
+    /// Fills the polygon formed by connecting `points` in order.
+    /// The last point is connected back to the first one.
+    pub fn fill_path(&mut self, points: &[Point], bg: impl Into<Background>) {
+        if points.len() < 3 {
+            return;
+        }
+
+        self.push_polyline(points);
+        self.builder.close();
+        self.draw_path(bg);
+    }
+
+    /// Draws a line through `points` in order with rounded joins and ends.
+    pub fn stroke_path(
+        &mut self,
+        points: &[Point],
+        width: f32,
+        bg: impl Into<Background>
+    ) {
+        if points.len() < 2 {
+            return;
+        }
+
+        let stroke = Stroke {
+            width,
+            line_join: LineJoin::Round,
+            line_cap: LineCap::Round,
+            ..Stroke::default()
+        };
+
+        self.push_polyline(points);
+        self.draw_stroke(bg, &stroke);
+    }
+
+    pub fn fill_quad(&mut self, rect: Rect, style: &QuadStyle) {
+        if rect.width <= 0f32 || rect.height <= 0f32 {
+            return;
+        }
+
+        self.push_quad(rect, style.border_radius);
+        self.draw_path(style.background.clone());
+
+        // A border wider than half the quad would cover it completely
+        // anyway and would leave us with a negative inner rect.
+        let border_width = style.border_width.min(rect.width.min(rect.height) / 2f32);
+
+        if border_width > 0f32 {
+            // Keep the border inside the bounds of the quad since
+            // the stroke is centered on the path.
+            let half = border_width / 2f32;
+            let rect = Rect {
+                x: rect.x + half,
+                y: rect.y + half,
+                width: rect.width - border_width,
+                height: rect.height - border_width
+            };
+            let radius = BorderRadius(
+                style.border_radius.0.map(|r| (r - half).max(0f32))
+            );
+
+            self.push_quad(rect, radius);
+            self.draw_stroke(
+                style.border_color.clone(),
+                &Stroke {
+                    width: border_width,
+                    ..Stroke::default()
+                }
+            );
+        }
+    }
+
+    #[inline]
+    fn push_polyline(&mut self, points: &[Point]) {
+        self.builder.move_to(points[0].x, points[0].y);
+
+        for point in &points[1..] {
+            self.builder.line_to(point.x, point.y);
+        }
+    }
+
+    #[inline]
+    fn push_quad(&mut self, rect: Rect, radius: BorderRadius) {
+        if radius.is_zero() {
+            self.builder.push_rect(rect.x, rect.y, rect.width, rect.height);
+        } else {
+            self.push_rounded_rect(rect, radius);
+        }
+    }
+
+    fn push_rounded_rect(&mut self, rect: Rect, radius: BorderRadius) {
+        // Distance of the control points from the corner when
+        // approximating a quarter circle with a cubic curve.
+        const KAPPA: f32 = 1f32 - 0.552_284_8;
+
+        let max = rect.width.min(rect.height) / 2f32;
+        let [tl, tr, br, bl] = radius.0.map(|r| r.clamp(0f32, max));
+
+        let left = rect.x;
+        let top = rect.y;
+        let right = rect.x + rect.width;
+        let bottom = rect.y + rect.height;
+
+        let builder = &mut self.builder;
+        builder.move_to(left + tl, top);
+
+        builder.line_to(right - tr, top);
+        builder.cubic_to(
+            right - tr * KAPPA, top,
+            right, top + tr * KAPPA,
+            right, top + tr
+        );
+
+        builder.line_to(right, bottom - br);
+        builder.cubic_to(
+            right, bottom - br * KAPPA,
+            right - br * KAPPA, bottom,
+            right - br, bottom
+        );
+
+        builder.line_to(left + bl, bottom);
+        builder.cubic_to(
+            left + bl * KAPPA, bottom,
+            left, bottom - bl * KAPPA,
+            left, bottom - bl
+        );
+
+        builder.line_to(left, top + tl);
+        builder.cubic_to(
+            left, top + tl * KAPPA,
+            left + tl * KAPPA, top,
+            left + tl, top
+        );
+
+        builder.close();
+    }
+
     fn draw_path(&mut self, bg: impl Into<Background>) {
         let builder = mem::take(&mut self.builder);
         let path = builder.finish().expect("invalid bounds");
-        let mut paint = Paint::default();
-        
-        match bg.into() {
-            Background::Color(color) => paint.set_color(color),
-            Background::LinearGradient(gradient) =>
-                paint.shader = Shader::LinearGradient(gradient)
-        }
 
-        paint.anti_alias = true;
+        #[cfg(feature = "profiling")]
+        {
+            self.commands += 1;
+        }
 
         self.pixmap.fill_path(
             &path,
-            &paint,
+            &paint(bg.into()),
             FillRule::Winding,
             Transform::identity(),
             None
@@ -98,6 +403,153 @@ impl<'a> DrawCtx<'a> {
 
         self.builder = path.clear();
     }
+
+    fn draw_stroke(&mut self, bg: impl Into<Background>, stroke: &Stroke) {
+        let builder = mem::take(&mut self.builder);
+        let path = builder.finish().expect("invalid bounds");
+
+        #[cfg(feature = "profiling")]
+        {
+            self.commands += 1;
+        }
+
+        self.pixmap.stroke_path(
+            &path,
+            &paint(bg.into()),
+            stroke,
+            Transform::identity(),
+            None
+        );
+
+        self.builder = path.clear();
+    }
+}
+
+impl QuadStyle {
+    #[inline]
+    pub fn new(background: impl Into<Background>) -> Self {
+        Self {
+            background: background.into(),
+            border_radius: BorderRadius::ZERO,
+            border_width: 0f32,
+            border_color: Color::TRANSPARENT.into()
+        }
+    }
+
+    #[inline]
+    pub fn with_border(
+        mut self,
+        width: f32,
+        color: impl Into<Background>
+    ) -> Self {
+        self.border_width = width;
+        self.border_color = color.into();
+
+        self
+    }
+
+    #[inline]
+    pub fn with_gradient_border(
+        self,
+        width: f32,
+        gradient: LinearGradient
+    ) -> Self {
+        self.with_border(width, gradient)
+    }
+
+    /// Rounds all four corners by the same amount.
+    #[inline]
+    pub fn rounded(mut self, radius: f32) -> Self {
+        self.border_radius = BorderRadius::all(radius);
+
+        self
+    }
+
+    #[inline]
+    pub fn rounded_corners(
+        mut self,
+        top_left: f32,
+        top_right: f32,
+        bottom_right: f32,
+        bottom_left: f32
+    ) -> Self {
+        self.border_radius = BorderRadius::new(
+            top_left,
+            top_right,
+            bottom_right,
+            bottom_left
+        );
+
+        self
+    }
+
+    /// Rounds the top left and top right corners.
+    /// The other two corners are left untouched.
+    #[inline]
+    pub fn rounded_top(mut self, radius: f32) -> Self {
+        self.border_radius.0[0] = radius;
+        self.border_radius.0[1] = radius;
+
+        self
+    }
+
+    /// Rounds the top right and bottom right corners.
+    /// The other two corners are left untouched.
+    #[inline]
+    pub fn rounded_right(mut self, radius: f32) -> Self {
+        self.border_radius.0[1] = radius;
+        self.border_radius.0[2] = radius;
+
+        self
+    }
+
+    /// Rounds the bottom right and bottom left corners.
+    /// The other two corners are left untouched.
+    #[inline]
+    pub fn rounded_bottom(mut self, radius: f32) -> Self {
+        self.border_radius.0[2] = radius;
+        self.border_radius.0[3] = radius;
+
+        self
+    }
+
+    /// Rounds the top left and bottom left corners.
+    /// The other two corners are left untouched.
+    #[inline]
+    pub fn rounded_left(mut self, radius: f32) -> Self {
+        self.border_radius.0[3] = radius;
+        self.border_radius.0[0] = radius;
+
+        self
+    }
+}
+
+impl Output {
+    /// Dots per inch along the horizontal axis of the panel or `None`
+    /// if the physical size or the current mode of the output is unknown.
+    #[inline]
+    pub fn dpi(&self) -> Option<f32> {
+        if self.physical_size.width <= 0f32 || self.pixel_size.width <= 0f32 {
+            return None;
+        }
+
+        // Both sizes are untransformed, so rotation and scaling don't matter.
+        Some(self.pixel_size.width / (self.physical_size.width / 25.4f32))
+    }
+}
+
+fn paint(bg: Background) -> Paint<'static> {
+    let mut paint = Paint::default();
+
+    match bg {
+        Background::Color(color) => paint.set_color(color),
+        Background::LinearGradient(gradient) =>
+            paint.shader = Shader::LinearGradient(gradient)
+    }
+
+    paint.anti_alias = true;
+
+    paint
 }
 
 impl From<Color> for Background {
@@ -113,3 +565,161 @@ impl From<LinearGradient> for Background {
         Self::LinearGradient(value)
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use tiny_skia::{GradientStop, SpreadMode};
+
+    use crate::widget::{
+        flex::{Flex, flex},
+        date_time::DateTime
+    };
+    use super::*;
+
+    /// Asserts that the pixel at `x`, `y` is `expected`, allowing for
+    /// small differences caused by anti-aliasing and interpolation.
+    pub(crate) fn assert_pixel(pixmap: &Pixmap, x: u32, y: u32, expected: Color) {
+        let actual = pixmap.pixel(x, y).unwrap().demultiply();
+        let expected = expected.to_color_u8();
+
+        let channels = [
+            (actual.red(), expected.red()),
+            (actual.green(), expected.green()),
+            (actual.blue(), expected.blue()),
+            (actual.alpha(), expected.alpha())
+        ];
+
+        assert!(
+            channels.iter().all(|(a, b)| a.abs_diff(*b) <= 4),
+            "pixel at ({}, {}) is {:?}, expected {:?}",
+            x, y, actual, expected
+        );
+    }
+
+    /// A horizontal gradient from red at `x = 0` to blue at `x = width`.
+    fn red_to_blue(width: f32) -> LinearGradient {
+        let Some(Shader::LinearGradient(gradient)) = LinearGradient::new(
+            tiny_skia::Point::from_xy(0f32, 0f32),
+            tiny_skia::Point::from_xy(width, 0f32),
+            vec![
+                GradientStop::new(0f32, Color::from_rgba8(255, 0, 0, 255)),
+                GradientStop::new(1f32, Color::from_rgba8(0, 0, 255, 255))
+            ],
+            SpreadMode::Pad,
+            Transform::identity()
+        ) else {
+            panic!("expected a linear gradient");
+        };
+
+        gradient
+    }
+
+    /// The color of [`red_to_blue`] at the center of the pixel column `x`.
+    fn red_to_blue_at(width: f32, x: u32) -> Color {
+        let t = (x as f32 + 0.5f32) / width;
+
+        Color::from_rgba(1f32 - t, 0f32, t, 1f32).unwrap()
+    }
+
+    #[test]
+    fn dpi_of_rotated_and_scaled_output() {
+        // A 4K panel rotated by 90 degrees. The logical size corresponds to
+        // a fractional scale of 1.5, which is advertised as an integer scale of 2.
+        let output = Output {
+            logical_size: Size::new(1440f32, 2560f32),
+            scale_factor: 2,
+            pixel_size: Size::new(3840f32, 2160f32),
+            physical_size: Size::new(609.6f32, 342.9f32)
+        };
+
+        assert!((output.dpi().unwrap() - 160f32).abs() < 0.01f32);
+
+        let unknown = Output { physical_size: Size::ZERO, ..output };
+        assert_eq!(unknown.dpi(), None);
+
+        let no_mode = Output { pixel_size: Size::ZERO, ..output };
+        assert_eq!(no_mode.dpi(), None);
+    }
+
+    #[test]
+    fn render_styled_flex() {
+        const WIDTH: f32 = 120f32;
+
+        let root = flex!(
+            Flex::row()
+                .padding(5f32)
+                .style(QuadStyle::new(red_to_blue(WIDTH)).rounded(10f32)),
+            DateTime::default()
+        );
+
+        let mut ui = Ui::new(Box::new(root));
+        let theme = Theme::light();
+        let pixmap = ui.render_to_pixmap(Size::new(WIDTH, 30f32)).unwrap();
+
+        assert_eq!((pixmap.width(), pixmap.height()), (120, 30));
+
+        // The rounded corners are cut out of the quad.
+        for (x, y) in [(0, 0), (119, 0), (119, 29), (0, 29)] {
+            assert_pixel(&pixmap, x, y, theme.background());
+        }
+
+        // The gradient runs along the padding from left to right.
+        for x in [1, 3, 108, 115, 118] {
+            assert_pixel(&pixmap, x, 15, red_to_blue_at(WIDTH, x));
+        }
+
+        for x in [20, 60, 100] {
+            assert_pixel(&pixmap, x, 2, red_to_blue_at(WIDTH, x));
+        }
+
+        // The child is drawn inside the padding, on top of the quad.
+        for (x, y) in [(5, 5), (55, 15), (104, 24)] {
+            assert_pixel(&pixmap, x, y, theme.warm1);
+        }
+    }
+
+    #[test]
+    fn render_asymmetric_radii() {
+        let theme = Theme::light();
+        let flex = Flex::row().style(QuadStyle::new(theme.cold1).rounded_top(10f32));
+
+        let mut ui = Ui::new(Box::new(flex));
+        let pixmap = ui.render_to_pixmap(Size::new(60f32, 30f32)).unwrap();
+
+        // Only the top corners are cut out.
+        for (x, y) in [(0, 0), (1, 1), (59, 0), (58, 1)] {
+            assert_pixel(&pixmap, x, y, theme.background());
+        }
+
+        for (x, y) in [(0, 29), (59, 29), (0, 15), (30, 0), (30, 15)] {
+            assert_pixel(&pixmap, x, y, theme.cold1);
+        }
+    }
+
+    #[test]
+    fn render_gradient_border() {
+        const WIDTH: f32 = 120f32;
+
+        let theme = Theme::light();
+        let flex = Flex::row().style(
+            QuadStyle::new(theme.cold1).with_gradient_border(4f32, red_to_blue(WIDTH))
+        );
+
+        let mut ui = Ui::new(Box::new(flex));
+        let pixmap = ui.render_to_pixmap(Size::new(WIDTH, 30f32)).unwrap();
+
+        // Both ends of the border follow the gradient.
+        for (x, y) in [(0, 15), (2, 15), (117, 15), (119, 15)] {
+            assert_pixel(&pixmap, x, y, red_to_blue_at(WIDTH, x));
+        }
+
+        for x in [10, 60, 110] {
+            assert_pixel(&pixmap, x, 1, red_to_blue_at(WIDTH, x));
+            assert_pixel(&pixmap, x, 28, red_to_blue_at(WIDTH, x));
+        }
+
+        for (x, y) in [(5, 15), (60, 15), (114, 15)] {
+            assert_pixel(&pixmap, x, y, theme.cold1);
+        }
+    }
+}
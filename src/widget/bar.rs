@@ -1,7 +1,7 @@
 use crate::{
-    geometry::Size,
+    geometry::{Size, Rect},
     positioner::Positioner,
-    ui::DrawCtx
+    ui::{DrawCtx, LayoutCtx}
 };
 use super::{
     size_constraints::SizeConstraints,
@@ -10,12 +10,14 @@ use super::{
     music::Music,
     cpu::Cpu,
     ram::Ram,
-    flex::{Flex, Alignment},
+    flex::{Flex, Alignment, flex},
+    divider::Divider,
     Widget
 };
 
 const PADDING: f32 = 6f32;
 const SPACING: f32 = 10f32;
+const DIVIDER_INSET: f32 = 4f32;
 
 pub struct Bar {
     modules: Flex
@@ -23,38 +25,60 @@ pub struct Bar {
 
 impl Bar {
     pub fn new() -> Self {
-        let left = Flex::row()
-            .spacing(SPACING)
-            .with_non_flex(Workspaces::new())
-            .with_non_flex(DateTime::default());
+        let left = flex!(
+            Flex::row().spacing(SPACING),
+            Workspaces::new(),
+            DateTime::default()
+        );
 
-        let middle = Flex::row()
-            .spacing(SPACING)
-            .with_non_flex(Music::default());
+        let middle = flex!(
+            Flex::row().spacing(SPACING),
+            Music::default()
+        );
 
-        let right = Flex::row()
-            .spacing(SPACING)
-            .main_alignment(Alignment::End)
-            .with_non_flex(Cpu::default())
-            .with_non_flex(Ram::default());
+        let right = flex!(
+            Flex::row()
+                .spacing(SPACING)
+                .main_alignment(Alignment::End),
+            Cpu::default(),
+            Ram::default()
+        );
 
         Self {
-            modules: Flex::row()
-                .spacing(SPACING)
-                .padding(PADDING)
-                .with_flex(left, 1f32)
-                .with_flex(middle, 2f32)
-                .with_flex(right, 1f32)
+            modules: flex!(
+                Flex::row()
+                    .spacing(SPACING)
+                    .padding(PADDING),
+                left => 1f32,
+                Divider::vertical().inset(DIVIDER_INSET),
+                middle => 2f32,
+                Divider::vertical().inset(DIVIDER_INSET),
+                right => 1f32
+            )
         }
     }
 }
 
 impl Widget for Bar {
-    fn layout(&mut self, bounds: SizeConstraints) -> Size {
-        self.modules.layout(bounds)
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: SizeConstraints) -> Size {
+        self.modules.layout(ctx, bounds)
+    }
+
+    #[inline]
+    fn intrinsic_size(&self) -> Size {
+        self.modules.intrinsic_size()
+    }
+
+    #[inline]
+    fn update(&mut self) {
+        self.modules.update()
     }
 
     fn draw(&mut self, ctx: &mut DrawCtx, positioner: Positioner) {
         self.modules.draw(ctx, positioner)
     }
+
+    fn children(&mut self, f: &mut dyn FnMut(&mut dyn Widget, Rect)) {
+        self.modules.children(f)
+    }
 }
@@ -1,24 +1,99 @@
+use std::time::{Duration, Instant};
+
 use crate::{
-    geometry::Size,
+    geometry::{Size, Rect},
     positioner::Positioner,
-    ui::DrawCtx
+    ui::{DrawCtx, LayoutCtx},
+    sys_info::{CpuStat, CpuUsage}
 };
 use super::{
     size_constraints::SizeConstraints,
     Widget
 };
 
+const CORE_SPACING: f32 = 1f32;
+/// Redraws that happen sooner than this after the previous sample
+/// keep showing it instead of shortening the window. Shorter than the
+/// redraw interval of the bar so that every periodic redraw samples.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Draws one bar per core showing its usage between the last two samples.
 #[derive(Default)]
 pub struct Cpu {
-
+    previous: Option<(CpuStat, Instant)>,
+    usage: CpuUsage
 }
 
 impl Widget for Cpu {
-    fn layout(&mut self, bounds: SizeConstraints) -> Size {
-        bounds.constrain(Size { width: 100f32, height: 20f32 })
+    fn layout(&mut self, _ctx: &LayoutCtx, bounds: SizeConstraints) -> Size {
+        bounds.constrain(self.intrinsic_size())
+    }
+
+    #[inline]
+    fn intrinsic_size(&self) -> Size {
+        Size { width: 100f32, height: 20f32 }
+    }
+
+    fn update(&mut self) {
+        if let Some((_, sampled_at)) = &self.previous {
+            if sampled_at.elapsed() < SAMPLE_INTERVAL {
+                return;
+            }
+        }
+
+        let stat = match CpuStat::read() {
+            Ok(stat) => stat,
+            Err(err) => {
+                log::warn!("Failed to read CPU usage: {}", err);
+
+                return;
+            }
+        };
+
+        // The first sample only holds the times since boot. Nothing is
+        // shown until there is a second one to compare it against.
+        if let Some((previous, _)) = &self.previous {
+            self.usage = stat.usage(previous);
+        }
+
+        self.previous = Some((stat, Instant::now()));
     }
 
     fn draw(&mut self, ctx: &mut DrawCtx, positioner: Positioner) {
-        ctx.fill_rect(positioner.bounds, ctx.theme.cold1);
+        let bounds = positioner.bounds;
+        ctx.fill_rect(bounds, ctx.theme.overlay);
+
+        let count = self.usage.cores.len();
+
+        if count == 0 {
+            return;
+        }
+
+        let mut spacing = CORE_SPACING;
+        let mut width = (bounds.width - spacing * (count - 1) as f32) / count as f32;
+
+        // Not enough room to separate every core, pack them together instead.
+        if width <= 0f32 {
+            spacing = 0f32;
+            width = bounds.width / count as f32;
+        }
+
+        if width <= 0f32 {
+            return;
+        }
+
+        for (i, core) in self.usage.cores.iter().enumerate() {
+            let height = bounds.height * (*core as f32 / 100f32);
+
+            ctx.fill_rect(
+                Rect {
+                    x: bounds.x + (i as f32 * (width + spacing)),
+                    y: bounds.y + bounds.height - height,
+                    width,
+                    height
+                },
+                ctx.theme.cold1
+            );
+        }
     }
 }
@@ -1,7 +1,7 @@
 use crate::{
     geometry::Size,
     positioner::Positioner,
-    ui::DrawCtx
+    ui::{DrawCtx, LayoutCtx}
 };
 use super::{
     size_constraints::SizeConstraints,
@@ -14,8 +14,13 @@ pub struct DateTime {
 }
 
 impl Widget for DateTime {
-    fn layout(&mut self, bounds: SizeConstraints) -> Size {
-        bounds.constrain(Size { width: 100f32, height: 20f32 })
+    fn layout(&mut self, _ctx: &LayoutCtx, bounds: SizeConstraints) -> Size {
+        bounds.constrain(self.intrinsic_size())
+    }
+
+    #[inline]
+    fn intrinsic_size(&self) -> Size {
+        Size { width: 100f32, height: 20f32 }
     }
 
     fn draw(&mut self, ctx: &mut DrawCtx, positioner: Positioner) {
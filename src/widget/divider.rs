@@ -0,0 +1,132 @@
+use tiny_skia::Color;
+
+use crate::{
+    geometry::{Size, Rect, Axis},
+    positioner::Positioner,
+    ui::{DrawCtx, LayoutCtx},
+    theme::Theme
+};
+use super::{
+    size_constraints::SizeConstraints,
+    Widget
+};
+
+const THICKNESS: f32 = 1f32;
+
+pub struct Divider {
+    axis: Axis,
+    length: Option<f32>,
+    thickness: f32,
+    inset: f32,
+    color: fn(&Theme) -> Color
+}
+
+impl Divider {
+    /// A line running top to bottom. Use this to separate
+    /// the children of a row.
+    #[inline]
+    pub fn vertical() -> Self {
+        Self::new(Axis::Vertical)
+    }
+
+    /// A line running left to right. Use this to separate
+    /// the children of a column.
+    #[inline]
+    pub fn horizontal() -> Self {
+        Self::new(Axis::Horizontal)
+    }
+
+    /// Fixed length of the line. If not set, the divider
+    /// takes all the space available along its axis.
+    #[inline]
+    pub fn length(mut self, length: f32) -> Self {
+        self.length = Some(length);
+
+        self
+    }
+
+    #[inline]
+    pub fn thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+
+        self
+    }
+
+    /// Space left empty at both ends of the line.
+    #[inline]
+    pub fn inset(mut self, inset: f32) -> Self {
+        self.inset = inset;
+
+        self
+    }
+
+    #[inline]
+    pub fn color(mut self, color: fn(&Theme) -> Color) -> Self {
+        self.color = color;
+
+        self
+    }
+
+    #[inline]
+    fn new(axis: Axis) -> Self {
+        Self {
+            axis,
+            length: None,
+            thickness: THICKNESS,
+            inset: 0f32,
+            color: |theme| theme.muted
+        }
+    }
+}
+
+impl Widget for Divider {
+    fn layout(&mut self, _ctx: &LayoutCtx, bounds: SizeConstraints) -> Size {
+        let length = self.length.unwrap_or_else(|| {
+            let max = self.axis.main(bounds.max);
+
+            if max.is_finite() {
+                max
+            } else {
+                self.axis.main(bounds.min)
+            }
+        });
+
+        let (width, height) = self.axis.main_and_cross(length, self.thickness);
+
+        bounds.constrain(Size::new(width, height))
+    }
+
+    fn intrinsic_size(&self) -> Size {
+        let (width, height) = self.axis.main_and_cross(
+            self.length.unwrap_or(0f32),
+            self.thickness
+        );
+
+        Size::new(width, height)
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx, positioner: Positioner) {
+        let bounds = positioner.bounds;
+
+        let rect = match self.axis {
+            Axis::Horizontal => Rect {
+                x: bounds.x + self.inset,
+                y: bounds.y + (bounds.height - self.thickness) / 2f32,
+                width: bounds.width - (self.inset * 2f32),
+                height: self.thickness
+            },
+            Axis::Vertical => Rect {
+                x: bounds.x + (bounds.width - self.thickness) / 2f32,
+                y: bounds.y + self.inset,
+                width: self.thickness,
+                height: bounds.height - (self.inset * 2f32)
+            }
+        };
+
+        if rect.width <= 0f32 || rect.height <= 0f32 {
+            return;
+        }
+
+        ctx.fill_rect(rect, (self.color)(ctx.theme));
+    }
+}
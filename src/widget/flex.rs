@@ -1,13 +1,41 @@
 use crate::{
-    geometry::{Size, Rect},
+    geometry::{Size, Rect, Padding, Axis},
     positioner::Positioner,
-    ui::DrawCtx
+    ui::{DrawCtx, LayoutCtx, QuadStyle}
 };
 use super::{
     size_constraints::SizeConstraints,
     Widget
 };
 
+/// Builds a [`Flex`] from the given container followed by its children.
+/// Children are non-flex by default, use `child => factor` to give
+/// a child a flex factor.
+///
+/// ```ignore
+/// flex!(
+///     Flex::row().spacing(10f32),
+///     Cpu::default(),
+///     Music::default() => 1f32
+/// )
+/// ```
+macro_rules! flex {
+    (@child $flex:ident, $child:expr) => {
+        $flex.with_non_flex($child)
+    };
+    (@child $flex:ident, $child:expr, $factor:expr) => {
+        $flex.with_flex($child, $factor)
+    };
+    ($flex:expr $(, $child:expr $(=> $factor:expr)?)* $(,)?) => {{
+        let flex = $flex;
+        $(let flex = $crate::widget::flex::flex!(@child flex, $child $(, $factor)?);)*
+
+        flex
+    }};
+}
+
+pub(crate) use flex;
+
 pub struct Flex {
     children: Vec<(Box<dyn Widget>, f32)>,
     rects: Vec<Rect>,
@@ -15,7 +43,8 @@ pub struct Flex {
     main_alignment: Alignment,
     cross_alignment: Alignment,
     spacing: f32,
-    padding: f32
+    padding: Padding,
+    style: Option<QuadStyle>
 }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -25,12 +54,6 @@ pub enum Alignment {
     End
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
-pub enum Axis {
-    Horizontal,
-    Vertical
-}
-
 impl Flex {
     #[inline]
     pub fn row() -> Self {
@@ -43,8 +66,8 @@ impl Flex {
     }
 
     #[inline]
-    pub fn padding(mut self, padding: f32) -> Self {
-        self.padding = padding;
+    pub fn padding(mut self, padding: impl Into<Padding>) -> Self {
+        self.padding = padding.into();
 
         self
     }
@@ -56,6 +79,14 @@ impl Flex {
         self
     }
 
+    /// Draws a quad with the given style behind the children.
+    #[inline]
+    pub fn style(mut self, style: QuadStyle) -> Self {
+        self.style = Some(style);
+
+        self
+    }
+
     #[inline]
     pub fn cross_alignment(mut self, alignment: Alignment) -> Self {
         self.cross_alignment = alignment;
@@ -98,7 +129,8 @@ impl Flex {
             main_alignment: Alignment::Start,
             cross_alignment: Alignment::Center,
             spacing: 0f32,
-            padding: 0f32
+            padding: Padding::ZERO,
+            style: None
         }
     }
 }
@@ -106,7 +138,7 @@ impl Flex {
 impl Widget for Flex {
     // Simplified version of the Flutter flex layout algorithm:
     // https://api.flutter.dev/flutter/widgets/Flex-class.html
-    fn layout(&mut self, bounds: SizeConstraints) -> Size {
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: SizeConstraints) -> Size {
         let total_len = self.children.len();
 
         self.rects.clear();
@@ -116,9 +148,8 @@ impl Widget for Flex {
             self.rects.set_len(total_len);
         }
 
-        let bounds = bounds.shrink(
-            Size::new(self.padding * 2f32, self.padding * 2f32)
-        );
+        let outer = bounds;
+        let bounds = bounds.shrink(self.padding.size());
         let spacing = self.spacing * total_len.saturating_sub(1) as f32;
 
         let max_cross = self.axis.cross(bounds.max);
@@ -142,7 +173,7 @@ impl Widget for Flex {
                 Size::new(width, height)
             );
 
-            let size = child.layout(widget_bounds);
+            let size = child.layout(ctx, widget_bounds);
 
             let main_cross = self.axis.main_and_cross_size(size);
             available -= main_cross.0;
@@ -188,7 +219,7 @@ impl Widget for Flex {
                     Size::new(max_width, max_height)
                 );
 
-                let size = child.layout(widget_bounds);
+                let size = child.layout(ctx, widget_bounds);
 
                 let main_cross = self.axis.main_and_cross_size(size);
                 total_main += main_cross.0;
@@ -203,24 +234,33 @@ impl Widget for Flex {
             }
         }
 
-        let mut main = match self.main_alignment {
-            Alignment::Start => self.padding,
-            Alignment::Center => (self.axis.main(bounds.max) -
-                spacing -
-                total_main) /
-                2f32,
-            Alignment::End => self.axis.main(bounds.max) -
-                spacing -
-                total_main
+        let free_main = self.axis.main(bounds.max) - spacing - total_main;
+        let offset = match self.main_alignment {
+            Alignment::Start => 0f32,
+            Alignment::Center => free_main / 2f32,
+            Alignment::End => free_main
         };
 
+        let (width, height) = self.axis.main_and_cross(offset + total_main + spacing, cross);
+
+        // Same as the intrinsic size, the padding is part of our size.
+        let size = outer.constrain(Size::new(width, height) + self.padding.size());
+
+        // The children are placed inside the padding.
+        let content = Rect::from_size(size).inset(self.padding);
+        let (main_start, cross_start) = self.axis.main_and_cross_size(
+            Size::new(content.x, content.y)
+        );
+
+        let mut main = main_start + offset;
+
         // Position children
         for (i, rect) in self.rects.iter_mut().enumerate() {
             if i > 0 {
                 main += self.spacing;
             }
 
-            let (x, y) = self.axis.main_and_cross(main, self.padding);
+            let (x, y) = self.axis.main_and_cross(main, cross_start);
             rect.x = x;
             rect.y = y;
 
@@ -229,58 +269,53 @@ impl Widget for Flex {
             main += self.axis.main(rect.size());
         }
 
-        let (width, height) = self.axis.main_and_cross(main - self.padding, cross);
-        
-        bounds.constrain(Size::new(width, height))
+        size
     }
 
-    fn draw(&mut self, ctx: &mut DrawCtx, positioner: Positioner) {
-        for (i, (child, _)) in self.children.iter_mut().enumerate() {
-            let positioner = positioner.next(self.rects[i]);
+    // Flex children are measured like any other child, the
+    // space they would take from the flex factor is ignored.
+    // Like the size returned by layout, this includes the padding.
+    fn intrinsic_size(&self) -> Size {
+        let spacing = self.spacing * self.children.len().saturating_sub(1) as f32;
 
-            child.draw(ctx, positioner);
-        }
-    }
-}
+        let mut main = spacing;
+        let mut cross = 0f32;
 
-impl Axis {
-    #[inline]
-    pub fn flip(&self) -> Self {
-        match self {
-            Self::Horizontal => Self::Vertical,
-            Self::Vertical => Self::Horizontal
+        for (child, _) in &self.children {
+            let (child_main, child_cross) = self.axis.main_and_cross_size(
+                child.intrinsic_size()
+            );
+
+            main += child_main;
+            cross = cross.max(child_cross);
         }
+
+        let (width, height) = self.axis.main_and_cross(main, cross);
+
+        Size::new(width, height) + self.padding.size()
     }
 
-    #[inline]
-    pub fn main(&self, size: Size) -> f32 {
-        match self {
-            Self::Horizontal => size.width,
-            Self::Vertical => size.height
+    fn update(&mut self) {
+        for (child, _) in &mut self.children {
+            child.update();
         }
     }
 
-    #[inline]
-    pub fn cross(&self, size: Size) -> f32 {
-        match self {
-            Self::Horizontal => size.height,
-            Self::Vertical => size.width
+    fn draw(&mut self, ctx: &mut DrawCtx, positioner: Positioner) {
+        if let Some(style) = &self.style {
+            ctx.fill_quad(positioner.bounds, style);
         }
-    }
 
-    #[inline]
-    pub fn main_and_cross_size(&self, size: Size) -> (f32, f32) {
-        match self {
-            Self::Horizontal => (size.width, size.height),
-            Self::Vertical => (size.height, size.width)
+        for (i, (child, _)) in self.children.iter_mut().enumerate() {
+            let positioner = positioner.next(self.rects[i]);
+
+            child.draw(ctx, positioner);
         }
     }
 
-    #[inline]
-    pub fn main_and_cross(&self, main: f32, cross: f32) -> (f32, f32) {
-        match self {
-            Self::Horizontal => (main, cross),
-            Self::Vertical => (cross, main)
+    fn children(&mut self, f: &mut dyn FnMut(&mut dyn Widget, Rect)) {
+        for (i, (child, _)) in self.children.iter_mut().enumerate() {
+            f(child.as_mut(), self.rects[i]);
         }
     }
 }
@@ -299,3 +334,62 @@ impl Alignment {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ui::{Ui, LayoutCtx, tests::assert_pixel},
+        theme::Theme,
+        widget::{workspaces::Workspaces, divider::Divider}
+    };
+    use super::*;
+
+    #[test]
+    fn intrinsic_size_matches_unbounded_layout() {
+        let mut flex = flex!(
+            Flex::row().spacing(3f32).padding((2f32, 4f32)),
+            Workspaces::new().count(2),
+            Divider::vertical().length(10f32)
+        );
+
+        // Two 16px workspaces 3px apart, the divider and the spacing between them.
+        let expected = Size::new(35f32 + 3f32 + 1f32, 16f32) + Size::new(8f32, 4f32);
+
+        let theme = Theme::light();
+        let ctx = LayoutCtx { theme: &theme, output: None };
+
+        assert_eq!(flex.intrinsic_size(), expected);
+        assert_eq!(flex.layout(&ctx, SizeConstraints::UNBOUNDED), expected);
+
+        // Squeezing the layout doesn't change the intrinsic size.
+        flex.layout(&ctx, SizeConstraints::tight(Size::new(30f32, 10f32)));
+        assert_eq!(flex.intrinsic_size(), expected);
+    }
+
+    #[test]
+    fn draws_style() {
+        let theme = Theme::light();
+        let flex = Flex::row().style(
+            QuadStyle::new(theme.cold1)
+                .rounded(8f32)
+                .with_border(2f32, theme.warm2)
+        );
+
+        let mut ui = Ui::new(Box::new(flex));
+        let pixmap = ui.render_to_pixmap(Size::new(60f32, 30f32)).unwrap();
+
+        // Corners outside of the radius are left untouched.
+        for (x, y) in [(0, 0), (1, 1), (59, 0), (59, 29), (0, 29)] {
+            assert_pixel(&pixmap, x, y, theme.background());
+        }
+
+        // The border is drawn inside the bounds on every side.
+        for (x, y) in [(30, 0), (30, 1), (59, 15), (58, 15), (30, 29), (0, 15)] {
+            assert_pixel(&pixmap, x, y, theme.warm2);
+        }
+
+        for (x, y) in [(30, 15), (3, 15), (56, 15), (30, 2), (30, 27)] {
+            assert_pixel(&pixmap, x, y, theme.cold1);
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use tiny_skia::Color;
+
+use crate::{
+    geometry::{Size, Rect, Point},
+    positioner::Positioner,
+    ui::{DrawCtx, LayoutCtx},
+    theme::Theme
+};
+use super::{
+    size_constraints::SizeConstraints,
+    Widget
+};
+
+const POINT_COUNT: usize = 32;
+const LINE_WIDTH: f32 = 1.5f32;
+const FILL_ALPHA: f32 = 0.3f32;
+
+pub struct LineChart {
+    samples: Vec<f32>,
+    point_count: usize,
+    smoothing: usize,
+    range: Option<(f32, f32)>,
+    filled: bool,
+    line_width: f32,
+    color: fn(&Theme) -> Color,
+    points: Vec<Point>
+}
+
+impl LineChart {
+    pub fn new() -> Self {
+        Self {
+            samples: Vec::new(),
+            point_count: POINT_COUNT,
+            smoothing: 1,
+            range: None,
+            filled: false,
+            line_width: LINE_WIDTH,
+            color: |theme| theme.cold1,
+            points: Vec::new()
+        }
+    }
+
+    /// The maximum number of samples that are plotted.
+    /// Only the most recent ones are kept.
+    #[inline]
+    pub fn point_count(mut self, count: usize) -> Self {
+        self.point_count = count.max(2);
+
+        self
+    }
+
+    /// Averages each sample with the `window - 1` samples before it
+    /// to smooth out spikes. A window of 1 disables smoothing.
+    #[inline]
+    pub fn smoothing(mut self, window: usize) -> Self {
+        self.smoothing = window.max(1);
+
+        self
+    }
+
+    /// Scale the samples against a fixed range instead of
+    /// the minimum and maximum of the current samples.
+    #[inline]
+    pub fn range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min, max));
+
+        self
+    }
+
+    /// Fill the area between the line and the bottom of the chart.
+    #[inline]
+    pub fn filled(mut self, filled: bool) -> Self {
+        self.filled = filled;
+
+        self
+    }
+
+    #[inline]
+    pub fn line_width(mut self, width: f32) -> Self {
+        self.line_width = width;
+
+        self
+    }
+
+    #[inline]
+    pub fn color(mut self, color: fn(&Theme) -> Color) -> Self {
+        self.color = color;
+
+        self
+    }
+
+    /// Replaces the plotted samples. The changes are visible the
+    /// next time the widget is drawn. NaN and infinite samples are ignored.
+    pub fn set_samples(&mut self, samples: &[f32]) {
+        self.samples.clear();
+        self.samples.extend(samples.iter().copied().filter(|x| x.is_finite()));
+
+        let start = self.samples.len().saturating_sub(self.point_count);
+        self.samples.drain(..start);
+    }
+
+    /// Appends a single sample, dropping the oldest one if there are
+    /// already `point_count` samples. NaN and infinite samples are ignored.
+    pub fn push_sample(&mut self, sample: f32) {
+        if !sample.is_finite() {
+            return;
+        }
+
+        if self.samples.len() >= self.point_count {
+            self.samples.remove(0);
+        }
+
+        self.samples.push(sample);
+    }
+
+    fn smoothed(&self, index: usize) -> f32 {
+        let start = (index + 1).saturating_sub(self.smoothing);
+        let window = &self.samples[start..=index];
+
+        window.iter().sum::<f32>() / window.len() as f32
+    }
+
+    /// Scales the samples to fit inside `bounds` and stores their
+    /// positions in `points`. Returns the y coordinate of the baseline.
+    fn plot(&mut self, bounds: Rect) -> f32 {
+        let count = self.samples.len();
+
+        let (min, max) = self.range.unwrap_or_else(|| {
+            (0..count).map(|i| self.smoothed(i)).fold(
+                (f32::INFINITY, f32::NEG_INFINITY),
+                |(min, max), x| (min.min(x), max.max(x))
+            )
+        });
+
+        // Keep the line vertically centered when all samples are the same.
+        let (min, span) = if max - min > f32::EPSILON {
+            (min, max - min)
+        } else {
+            (min - 0.5f32, 1f32)
+        };
+
+        // Inset by half the line width so that the stroke isn't cut off at the edges.
+        let inset = self.line_width / 2f32;
+        let width = bounds.width - self.line_width;
+        let height = bounds.height - self.line_width;
+        let step = width / (count - 1) as f32;
+        let bottom = bounds.y + inset + height;
+
+        self.points.clear();
+
+        for i in 0..count {
+            let value = ((self.smoothed(i) - min) / span).clamp(0f32, 1f32);
+
+            self.points.push(Point::new(
+                bounds.x + inset + (i as f32 * step),
+                bottom - (value * height)
+            ));
+        }
+
+        bottom
+    }
+}
+
+impl Widget for LineChart {
+    fn layout(&mut self, _ctx: &LayoutCtx, bounds: SizeConstraints) -> Size {
+        let fill = |max: f32, min: f32| if max.is_finite() { max } else { min };
+
+        bounds.constrain(Size::new(
+            fill(bounds.max.width, bounds.min.width),
+            fill(bounds.max.height, bounds.min.height)
+        ))
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx, positioner: Positioner) {
+        let count = self.samples.len();
+
+        if count < 2 {
+            return;
+        }
+
+        let bottom = self.plot(positioner.bounds);
+        let color = (self.color)(ctx.theme);
+
+        if self.filled {
+            let first = self.points[0].x;
+            let last = self.points[count - 1].x;
+
+            self.points.push(Point::new(last, bottom));
+            self.points.push(Point::new(first, bottom));
+
+            let mut fill = color;
+            fill.apply_opacity(FILL_ALPHA);
+
+            ctx.fill_path(&self.points, fill);
+            self.points.truncate(count);
+        }
+
+        ctx.stroke_path(&self.points, self.line_width, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: Rect = Rect::new(0f32, 0f32, 20f32, 10f32);
+
+    fn plot(chart: &mut LineChart) -> Vec<Point> {
+        chart.plot(BOUNDS);
+
+        chart.points.clone()
+    }
+
+    #[test]
+    fn scales_to_min_and_max() {
+        let mut chart = LineChart::new().line_width(0f32);
+        chart.set_samples(&[1f32, 3f32, 2f32]);
+
+        assert_eq!(plot(&mut chart), [
+            Point::new(0f32, 10f32),
+            Point::new(10f32, 0f32),
+            Point::new(20f32, 5f32)
+        ]);
+    }
+
+    #[test]
+    fn scales_to_fixed_range() {
+        let mut chart = LineChart::new().line_width(0f32).range(0f32, 4f32);
+        chart.set_samples(&[1f32, 3f32, 2f32]);
+
+        assert_eq!(plot(&mut chart), [
+            Point::new(0f32, 7.5f32),
+            Point::new(10f32, 2.5f32),
+            Point::new(20f32, 5f32)
+        ]);
+    }
+
+    #[test]
+    fn centers_flat_line() {
+        let mut chart = LineChart::new().line_width(0f32);
+        chart.set_samples(&[2f32, 2f32]);
+
+        assert_eq!(plot(&mut chart), [
+            Point::new(0f32, 5f32),
+            Point::new(20f32, 5f32)
+        ]);
+    }
+
+    #[test]
+    fn smooths_samples() {
+        let mut chart = LineChart::new().line_width(0f32).smoothing(2);
+        chart.set_samples(&[1f32, 3f32, 2f32]);
+
+        // Smoothed to 1, 2 and 2.5.
+        let points = plot(&mut chart);
+
+        assert_eq!(points[0], Point::new(0f32, 10f32));
+        assert!((points[1].y - 10f32 / 3f32).abs() < 1e-4);
+        assert_eq!(points[2], Point::new(20f32, 0f32));
+    }
+
+    #[test]
+    fn insets_by_half_the_line_width() {
+        let mut chart = LineChart::new().line_width(2f32);
+        chart.set_samples(&[0f32, 1f32]);
+
+        assert_eq!(plot(&mut chart), [
+            Point::new(1f32, 9f32),
+            Point::new(19f32, 1f32)
+        ]);
+    }
+
+    #[test]
+    fn ignores_non_finite_samples() {
+        let mut chart = LineChart::new().point_count(3);
+        chart.set_samples(&[1f32, f32::NAN, 2f32, f32::INFINITY, 3f32]);
+        chart.push_sample(f32::NEG_INFINITY);
+        chart.push_sample(f32::NAN);
+
+        assert_eq!(chart.samples, [1f32, 2f32, 3f32]);
+
+        chart.push_sample(4f32);
+
+        assert_eq!(chart.samples, [2f32, 3f32, 4f32]);
+    }
+}
@@ -6,15 +6,34 @@ pub mod cpu;
 pub mod ram;
 pub mod music;
 pub mod flex;
+pub mod divider;
+pub mod line_chart;
 
 use crate::{
-    geometry::Size,
+    geometry::{Size, Rect},
     positioner::Positioner,
-    ui::DrawCtx
+    ui::{DrawCtx, LayoutCtx}
 };
 use size_constraints::SizeConstraints;
 
 pub trait Widget {
-    fn layout(&mut self, bounds: SizeConstraints) -> Size;
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: SizeConstraints) -> Size;
     fn draw(&mut self, ctx: &mut DrawCtx, positioner: Positioner);
+
+    /// The size this widget would like to have if it was given unbounded
+    /// constraints, without performing a layout. Widgets that fill the
+    /// available space report zero along the axes they fill.
+    fn intrinsic_size(&self) -> Size {
+        Size::ZERO
+    }
+
+    /// Refreshes the data the widget displays, e.g. by sampling the
+    /// system. Called before the bar is drawn, `draw` should only
+    /// render whatever was gathered here.
+    fn update(&mut self) { }
+
+    /// Calls `f` with every child of this widget and its rect,
+    /// relative to this widget, as computed by the last layout.
+    /// Only used for debugging purposes.
+    fn children(&mut self, _f: &mut dyn FnMut(&mut dyn Widget, Rect)) { }
 }
@@ -1,7 +1,7 @@
 use crate::{
     geometry::Size,
     positioner::Positioner,
-    ui::DrawCtx
+    ui::{DrawCtx, LayoutCtx}
 };
 use super::{
     size_constraints::SizeConstraints,
@@ -14,10 +14,15 @@ pub struct Music {
 }
 
 impl Widget for Music {
-    fn layout(&mut self, bounds: SizeConstraints) -> Size {
+    fn layout(&mut self, _ctx: &LayoutCtx, bounds: SizeConstraints) -> Size {
         bounds.constrain(Size { width: bounds.max.width, height: 20f32 })
     }
 
+    #[inline]
+    fn intrinsic_size(&self) -> Size {
+        Size { width: 0f32, height: 20f32 }
+    }
+
     fn draw(&mut self, ctx: &mut DrawCtx, positioner: Positioner) {
         ctx.fill_rect(positioner.bounds, ctx.theme.warm2);
     }
@@ -1,24 +1,77 @@
+use std::time::{Duration, Instant};
+
 use crate::{
-    geometry::Size,
+    geometry::{Size, Rect},
     positioner::Positioner,
-    ui::DrawCtx
+    ui::{DrawCtx, LayoutCtx},
+    sys_info::RamUsage
 };
 use super::{
     size_constraints::SizeConstraints,
     Widget
 };
 
+/// Shorter than the redraw interval of the bar so that every periodic redraw samples.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
 #[derive(Default)]
 pub struct Ram {
-
+    usage: RamUsage,
+    sampled_at: Option<Instant>
 }
 
 impl Widget for Ram {
-    fn layout(&mut self, bounds: SizeConstraints) -> Size {
-        bounds.constrain(Size { width: 100f32, height: 20f32 })
+    fn layout(&mut self, _ctx: &LayoutCtx, bounds: SizeConstraints) -> Size {
+        bounds.constrain(self.intrinsic_size())
+    }
+
+    #[inline]
+    fn intrinsic_size(&self) -> Size {
+        Size { width: 100f32, height: 20f32 }
+    }
+
+    fn update(&mut self) {
+        if self.sampled_at.is_some_and(|time| time.elapsed() < SAMPLE_INTERVAL) {
+            return;
+        }
+
+        match RamUsage::read() {
+            Ok(usage) => {
+                self.usage = usage;
+                self.sampled_at = Some(Instant::now());
+            }
+            Err(err) => log::warn!("Failed to read memory usage: {}", err)
+        }
     }
 
     fn draw(&mut self, ctx: &mut DrawCtx, positioner: Positioner) {
-        ctx.fill_rect(positioner.bounds, ctx.theme.cold3);
+        let bounds = positioner.bounds;
+        ctx.fill_rect(bounds, ctx.theme.overlay);
+
+        let usage = &self.usage;
+
+        if usage.total == 0 {
+            return;
+        }
+
+        // Stacked bar: memory in use followed by the reclaimable cache.
+        let total = usage.total as f32;
+        let used = usage.used() as f32 / total;
+        let cache = usage.cache().min(usage.available) as f32 / total;
+
+        let used_width = bounds.width * used;
+        ctx.fill_rect(
+            Rect { width: used_width, ..bounds },
+            ctx.theme.cold3
+        );
+
+        ctx.fill_rect(
+            Rect {
+                x: bounds.x + used_width,
+                width: bounds.width * cache,
+                ..bounds
+            },
+            ctx.theme.muted
+        );
     }
 }
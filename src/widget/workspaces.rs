@@ -1,9 +1,7 @@
-use tiny_skia::Color;
-
 use crate::{
     geometry::{Size, Circle},
     positioner::Positioner,
-    ui::DrawCtx
+    ui::{DrawCtx, LayoutCtx}
 };
 use super::{
     size_constraints::SizeConstraints,
@@ -15,42 +13,65 @@ const RADIUS: f32 = 8f32;
 const SPACING: f32 = 3f32;
 
 pub struct Workspaces {
-    radius: f32
+    radius: f32,
+    /// The radius clamped to the height given by the last layout.
+    layout_radius: f32,
+    count: usize
 }
 
 impl Workspaces {
     pub fn new() -> Self {
-        Self { radius: RADIUS }
+        Self {
+            radius: RADIUS,
+            layout_radius: RADIUS,
+            count: WORKSPACE_COUNT
+        }
+    }
+
+    #[inline]
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+
+        self
+    }
+
+    /// The space taken by all workspaces when drawn with the given `radius`.
+    fn size(&self, radius: f32) -> Size {
+        let diameter = radius * 2f32;
+        let count = self.count as f32;
+        let spacing = SPACING * self.count.saturating_sub(1) as f32;
+
+        Size {
+            width: (diameter * count) + spacing,
+            height: diameter
+        }
     }
 }
 
 impl Widget for Workspaces {
-    fn layout(&mut self, bounds: SizeConstraints) -> Size {
+    fn layout(&mut self, _ctx: &LayoutCtx, bounds: SizeConstraints) -> Size {
         let diameter = self.radius * 2f32;
         let diameter = diameter.clamp(bounds.min.height, bounds.max.height);
-        self.radius = diameter / 2f32;
+        self.layout_radius = diameter / 2f32;
 
-        let count = WORKSPACE_COUNT as f32;
-        let spacing = (SPACING * count) - 1f32;
-        let width = (diameter * count) + spacing;
-        let size = bounds.constrain(Size {
-            width,
-            height: diameter
-        });
+        bounds.constrain(self.size(self.layout_radius))
+    }
 
-        size
+    #[inline]
+    fn intrinsic_size(&self) -> Size {
+        self.size(self.radius)
     }
 
     fn draw(&mut self, ctx: &mut DrawCtx, positioner: Positioner) {
         let bounds = positioner.bounds;
-        let y = bounds.y + self.radius;
-        let mut x = bounds.x + self.radius;
+        let y = bounds.y + self.layout_radius;
+        let mut x = bounds.x + self.layout_radius;
 
-        for _ in 0..WORKSPACE_COUNT {
-            let circle = Circle { x, y, radius: self.radius };
-            ctx.fill_circle(circle, Color::BLACK);
+        for _ in 0..self.count {
+            let circle = Circle { x, y, radius: self.layout_radius };
+            ctx.fill_circle(circle, ctx.theme.primary());
             
-            x += (self.radius * 2f32) + SPACING;
+            x += (self.layout_radius * 2f32) + SPACING;
         }
     }
 }